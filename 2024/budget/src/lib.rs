@@ -0,0 +1,125 @@
+use core::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The reason a solver gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Exhausted {
+    Steps(usize),
+    Deadline,
+    Cancelled,
+}
+
+impl Display for Exhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Steps(e) => write!(f, "Gave up after {e} steps"),
+            Self::Deadline => write!(f, "Gave up after reaching the deadline"),
+            Self::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// A handle to cancel all `Budget`s it was handed to, e. g. from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// Looking up the time is comparatively expensive, so the deadline is only checked every this
+// many steps.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// An upper bound on the work a solver may do. Long-running loops call `tick()` once per
+/// iteration and give up as soon as it returns an error.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    max_steps: Option<usize>,
+    deadline: Option<Instant>,
+    token: Option<CancelToken>,
+    steps: usize,
+}
+
+impl Budget {
+    /// A budget without any limits. `tick()` never fails on it.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of steps to `max_steps`.
+    pub fn with_steps(self, max_steps: usize) -> Self {
+        Self { max_steps: Some(max_steps), ..self }
+    }
+
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self { deadline: Some(deadline), ..self }
+    }
+
+    /// Set the deadline to `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_cancel_token(self, token: CancelToken) -> Self {
+        Self { token: Some(token), ..self }
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Account for one step of work. Returns an error if the step limit or deadline have been
+    /// exceeded, or if the budget was cancelled.
+    pub fn tick(&mut self) -> Result<(), Exhausted> {
+        self.steps += 1;
+        if self.max_steps.is_some_and(|max| self.steps > max) {
+            return Err(Exhausted::Steps(self.steps - 1));
+        }
+        if self.token.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return Err(Exhausted::Cancelled);
+        }
+        if self.steps % DEADLINE_CHECK_INTERVAL == 1 && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(Exhausted::Deadline);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let mut unlimited = Budget::unlimited();
+        assert!((0..10_000).all(|_| unlimited.tick().is_ok()));
+
+        let mut steps = Budget::unlimited().with_steps(3);
+        assert_eq!((0..5).map(|_| steps.tick()).collect::<Vec<_>>(), [Ok(()), Ok(()), Ok(()), Err(Exhausted::Steps(3)), Err(Exhausted::Steps(4))]);
+
+        let mut deadline = Budget::unlimited().with_deadline(Instant::now());
+        assert_eq!(deadline.tick(), Err(Exhausted::Deadline));
+
+        let token = CancelToken::new();
+        let mut cancelled = Budget::unlimited().with_cancel_token(token.clone());
+        assert_eq!(cancelled.tick(), Ok(()));
+        token.cancel();
+        assert_eq!(cancelled.tick(), Err(Exhausted::Cancelled));
+    }
+}
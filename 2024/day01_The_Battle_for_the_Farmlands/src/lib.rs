@@ -1,30 +1,66 @@
 use core::fmt::Display;
+use core::hash::{Hash, Hasher};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::num::ParseIntError;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
-    ParseCharError(char)
+    ConfigLineMalformed(String),
+    InvalidUtf8,
+    ParseCharError(char),
+    ParseIntError(ParseIntError),
+    ReadError(String),
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::ConfigLineMalformed(e) => write!(f, "Unable to parse config line: {e}. It should be formatted like \"creatures: A=0 B=1\", \"empty: x\", or \"bonus: 2=2 3=6\"."),
+            Self::InvalidUtf8 => write!(f, "Input is not valid UTF-8"),
             Self::ParseCharError(e) => write!(f, "Unable to parse into creature: {e}"),
+            Self::ParseIntError(e) => write!(f, "Unable to parse into a number: {e:?}"),
+            Self::ReadError(e) => write!(f, "Unable to read input: {e}"),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum Creature{ Ant, Beetle, Cockroach, Dragonfly, None }
+impl From<ParseIntError> for ParseError {
+    fn from(value: ParseIntError) -> Self {
+        Self::ParseIntError(value)
+    }
+}
+
+/// A single creature in the battle line, identified by its letter alone. `None` marks an empty
+/// spot (`x` in the notes). The creatures from the notes have variants of their own, any other
+/// letter a `Catalogue` knows about is `Other`. How many potions a creature needs is up to the
+/// `Catalogue`.
+#[derive(Clone, Copy, Debug)]
+pub enum Creature{ Ant, Beetle, Cockroach, Dragonfly, Other(char), None }
+
+// Two creatures are the same if their letters are, even if one of them was built as `Other`.
+impl PartialEq for Creature {
+    fn eq(&self, other: &Self) -> bool {
+        self.letter() == other.letter()
+    }
+}
+
+impl Eq for Creature {}
+
+impl Hash for Creature {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.letter().hash(state);
+    }
+}
 
 impl TryFrom<char> for Creature {
     type Error = ParseError;
 
+    /// The creature denoted by `value` under the default rules.
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'A' => Ok(Self::Ant),
-            'B' => Ok(Self::Beetle),
-            'C' => Ok(Self::Cockroach),
-            'D' => Ok(Self::Dragonfly),
+            'A' | 'B' | 'C' | 'D' => Ok(Self::from_letter(value)),
             'x' => Ok(Self::None),
             e => Err(Self::Error::ParseCharError(e)),
         }
@@ -32,9 +68,34 @@ impl TryFrom<char> for Creature {
 }
 
 impl Creature {
-    fn required_potions(&self) -> usize {
+    /// The creature with the given `letter`, using its own variant if it has one.
+    pub fn from_letter(letter: char) -> Self {
+        match letter {
+            'A' => Self::Ant,
+            'B' => Self::Beetle,
+            'C' => Self::Cockroach,
+            'D' => Self::Dragonfly,
+            other => Self::Other(other),
+        }
+    }
+
+    /// The letter of this creature, or `None` for an empty spot.
+    pub fn letter(&self) -> Option<char> {
+        match self {
+            Self::Ant => Some('A'),
+            Self::Beetle => Some('B'),
+            Self::Cockroach => Some('C'),
+            Self::Dragonfly => Some('D'),
+            Self::Other(letter) => Some(*letter),
+            Self::None => None,
+        }
+    }
+
+    /// The number of potions needed to defeat this creature on its own under the default rules.
+    /// Creatures not known to them need none. Use `Catalogue::potions()` for other rules.
+    pub fn required_potions(&self) -> usize {
         match self {
-            Self::Ant | Self::None => 0,
+            Self::Ant | Self::Other(_) | Self::None => 0,
             Self::Beetle => 1,
             Self::Cockroach => 3,
             Self::Dragonfly => 5,
@@ -42,21 +103,320 @@ impl Creature {
     }
 }
 
-fn required_potions_for_group(pair: &[Creature]) -> usize {
-    pair.iter().map(|c| c.required_potions()).sum::<usize>() +
-        match pair.iter().filter(|c| c != &&Creature::None).count() {
+/// The rules of battle: The letters used for the creatures, the number of potions each of them
+/// needs, the letter marking empty spots, and the extra potions needed for groups of several
+/// creatures fighting together, by the number of creatures in the group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Catalogue {
+    creatures: HashMap<char, usize>,
+    empty: char,
+    group_bonus: HashMap<usize, usize>,
+}
+
+impl Default for Catalogue {
+    /// The rules from the notes: Ants, Beetles, Cockroaches and Dragonflies need 0, 1, 3, and 5
+    /// potions respectively. Groups of 2 need 2 extra potions, groups of 3 need 6.
+    fn default() -> Self {
+        Self {
+            creatures: HashMap::from([('A', 0), ('B', 1), ('C', 3), ('D', 5)]),
+            empty: 'x',
+            group_bonus: HashMap::from([(2, 2), (3, 6)]),
+        }
+    }
+}
+
+impl TryFrom<&str> for Catalogue {
+    type Error = ParseError;
+
+    /// Parse a config section like this:
+    /// ```text
+    /// creatures: A=0 B=1 C=3 D=5
+    /// empty: x
+    /// bonus: 2=2 3=6
+    /// ```
+    /// All lines are optional. Anything left out is taken from `Catalogue::default()`. The
+    /// `creatures` and `bonus` lines replace the default tables as a whole, so the creatures
+    /// and bonuses not listed there don't exist. Several lines of the same kind add up.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let Self { mut creatures, mut empty, mut group_bonus } = Self::default();
+        let mut creatures_given = false;
+        let mut bonus_given = false;
+        for line in value.lines().filter(|line| !line.trim().is_empty()) {
+            let malformed = || ParseError::ConfigLineMalformed(line.to_string());
+            let (key, entries) = line.split_once(':').ok_or_else(malformed)?;
+            match (key.trim(), entries.trim()) {
+                ("creatures", entries) => {
+                    if !std::mem::replace(&mut creatures_given, true) {
+                        creatures.clear();
+                    }
+                    for entry in entries.split_whitespace() {
+                        let (letter, potions) = entry.split_once('=').ok_or_else(malformed)?;
+                        let mut letter = letter.chars();
+                        match (letter.next(), letter.next()) {
+                            (Some(letter), None) => { creatures.insert(letter, potions.parse()?); },
+                            _ => return Err(malformed()),
+                        }
+                    }
+                },
+                ("empty", letter) => {
+                    let mut letter = letter.chars();
+                    match (letter.next(), letter.next()) {
+                        (Some(letter), None) => empty = letter,
+                        _ => return Err(malformed()),
+                    }
+                },
+                ("bonus", entries) => {
+                    if !std::mem::replace(&mut bonus_given, true) {
+                        group_bonus.clear();
+                    }
+                    for entry in entries.split_whitespace() {
+                        let (count, bonus) = entry.split_once('=').ok_or_else(malformed)?;
+                        group_bonus.insert(count.parse()?, bonus.parse()?);
+                    }
+                },
+                _ => return Err(malformed()),
+            }
+        }
+        Ok(Self { creatures, empty, group_bonus })
+    }
+}
+
+impl Catalogue {
+    /// The creature denoted by `letter`.
+    pub fn creature(&self, letter: char) -> Result<Creature, ParseError> {
+        if letter == self.empty {
+            Ok(Creature::None)
+        } else if self.creatures.contains_key(&letter) {
+            Ok(Creature::from_letter(letter))
+        } else {
+            Err(ParseError::ParseCharError(letter))
+        }
+    }
+
+    /// The number of potions needed to defeat `creature` on its own. Creatures not in the
+    /// catalogue need none.
+    pub fn potions(&self, creature: Creature) -> usize {
+        creature.letter().and_then(|letter| self.creatures.get(&letter)).copied().unwrap_or(0)
+    }
+
+    /// The extra potions needed for a group of `count` actual creatures.
+    pub fn group_bonus(&self, count: usize) -> usize {
+        self.group_bonus.get(&count).copied().unwrap_or(0)
+    }
+
+    /// The number of potions needed to defeat a group of creatures fighting together, including
+    /// the bonus for the number of actual creatures in it.
+    pub fn required_potions_for_group(&self, group: &[Creature]) -> usize {
+        required_potions_with_bonus(group, self, |count| self.group_bonus(count))
+    }
+}
+
+/// The number of potions needed to defeat a group of creatures fighting together, under the
+/// default rules. Same as `Catalogue::default().required_potions_for_group(group)`.
+pub fn required_potions_for_group(group: &[Creature]) -> usize {
+    group.iter().map(|c| c.required_potions()).sum::<usize>() +
+        match group.iter().filter(|c| c != &&Creature::None).count() {
             3 => 6,
             2 => 2,
             _ => 0,
         }
 }
 
+/// The number of potions needed to defeat a group of creatures fighting together. Each creature
+/// needs the potions given by the `catalogue`, and `bonus` gives the extra potions needed,
+/// depending on the number of actual (not `None`) creatures in the group.
+pub fn required_potions_with_bonus(group: &[Creature], catalogue: &Catalogue, bonus: impl Fn(usize) -> usize) -> usize {
+    group.iter().map(|c| catalogue.potions(*c)).sum::<usize>() +
+        bonus(group.iter().filter(|c| c != &&Creature::None).count())
+}
+
+/// The potions needed for a battle, in total and for each group in order.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Battle {
+    pub total: usize,
+    pub groups: Vec<usize>,
+}
+
+/// Split the `creatures` into consecutive groups of `group_size` (the last one may be smaller)
+/// and count the potions needed for each of them, with the potions per creature from the
+/// `catalogue` and the extra potions given by `bonus` as in `required_potions_with_bonus()`.
+///
+/// Panics if `group_size` is 0.
+pub fn battle(creatures: &[Creature], group_size: usize, catalogue: &Catalogue, bonus: impl Fn(usize) -> usize) -> Battle {
+    let groups: Vec<_> = creatures.chunks(group_size).map(|group| required_potions_with_bonus(group, catalogue, &bonus)).collect();
+    Battle { total: groups.iter().sum(), groups }
+}
+
+/// A partition of the battle line into consecutive groups, and the potions needed for it.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Partition<'a> {
+    pub total: usize,
+    pub groups: Vec<&'a [Creature]>,
+}
+
+/// The partition of `creatures` into consecutive groups of at most `max_group_size` that needs
+/// the fewest potions under the rules of the `catalogue`.
+///
+/// Panics if `max_group_size` is 0.
+pub fn min_potions_partition<'a>(creatures: &'a [Creature], max_group_size: usize, catalogue: &Catalogue) -> Partition<'a> {
+    optimal_partition(creatures, max_group_size, catalogue, |candidate, best| candidate < best)
+}
+
+/// Like `min_potions_partition()`, but needing the most potions, to plan for the worst case.
+pub fn max_potions_partition<'a>(creatures: &'a [Creature], max_group_size: usize, catalogue: &Catalogue) -> Partition<'a> {
+    optimal_partition(creatures, max_group_size, catalogue, |candidate, best| candidate > best)
+}
+
+fn optimal_partition<'a>(creatures: &'a [Creature], max_group_size: usize, catalogue: &Catalogue, is_better: fn(usize, usize) -> bool) -> Partition<'a> {
+    assert!(max_group_size > 0, "Group size must be positive");
+    // best[end] is the optimal number of potions for the first `end` creatures, and the start of
+    // the last group in that optimal partition.
+    let mut best: Vec<(usize, usize)> = vec![(0, 0)];
+    for end in 1..=creatures.len() {
+        let mut best_here = None;
+        for start in end.saturating_sub(max_group_size)..end {
+            let potions = best[start].0 + catalogue.required_potions_for_group(&creatures[start..end]);
+            if best_here.is_none_or(|(best_potions, _)| is_better(potions, best_potions)) {
+                best_here = Some((potions, start));
+            }
+        }
+        best.push(best_here.unwrap_or_default());
+    }
+
+    let mut groups = Vec::new();
+    let mut end = creatures.len();
+    while end > 0 {
+        let start = best[end].1;
+        groups.push(&creatures[start..end]);
+        end = start;
+    }
+    groups.reverse();
+    Partition { total: best[creatures.len()].0, groups }
+}
+
+/// How to treat notes spanning several lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Battlefields {
+    /// Every line is a battlefield of its own.
+    Independent,
+    /// All lines together form a single battle line.
+    Concatenated,
+}
+
+/// The battles described by the notes, one per battlefield, as in `battle()`. Groups are formed
+/// and the bonus is taken from the `catalogue`.
+pub fn battles(input: &str, group_size: usize, catalogue: &Catalogue, battlefields: Battlefields) -> Result<Vec<Battle>, ParseError> {
+    let parse = |line: &str| line.chars().map(|c| catalogue.creature(c)).collect::<Result<Vec<_>, _>>();
+    let bonus = |count| catalogue.group_bonus(count);
+    match battlefields {
+        Battlefields::Independent => input.lines().map(|line| Ok(battle(&parse(line)?, group_size, catalogue, bonus))).collect(),
+        Battlefields::Concatenated => Ok(vec![battle(&parse(&input.lines().collect::<String>())?, group_size, catalogue, bonus)]),
+    }
+}
+
+// Counts the potions for a battle one creature at a time, so we never need to keep them all.
+struct Tally<'a> {
+    catalogue: &'a Catalogue,
+    group_size: usize,
+    total: usize,
+    group_potions: usize,
+    group_len: usize,
+    group_creatures: usize,
+}
+
+impl<'a> Tally<'a> {
+    fn new(catalogue: &'a Catalogue, group_size: usize) -> Self {
+        assert!(group_size > 0, "Group size must be positive");
+        Self { catalogue, group_size, total: 0, group_potions: 0, group_len: 0, group_creatures: 0 }
+    }
+
+    fn push(&mut self, creature: Creature) {
+        self.group_potions += self.catalogue.potions(creature);
+        self.group_len += 1;
+        if creature != Creature::None {
+            self.group_creatures += 1;
+        }
+        if self.group_len == self.group_size {
+            self.end_group();
+        }
+    }
+
+    fn end_group(&mut self) {
+        if self.group_len > 0 {
+            self.total += self.group_potions + self.catalogue.group_bonus(self.group_creatures);
+        }
+        self.group_potions = 0;
+        self.group_len = 0;
+        self.group_creatures = 0;
+    }
+
+    /// End the current battle and return its total, starting over for the next one.
+    fn finish(&mut self) -> usize {
+        self.end_group();
+        std::mem::take(&mut self.total)
+    }
+}
+
+/// Like `battles()`, but reads the notes from `reader` bit by bit, instead of requiring them all
+/// in memory at once. Only the total for each battlefield is returned.
+///
+/// Panics if `group_size` is 0.
+pub fn battle_totals(mut reader: impl BufRead, group_size: usize, catalogue: &Catalogue, battlefields: Battlefields) -> Result<Vec<usize>, ParseError> {
+    let mut totals = Vec::new();
+    let mut tally = Tally::new(catalogue, group_size);
+    let mut line_empty = true;
+    // The bytes of a character that is split between two reads.
+    let mut partial = Vec::with_capacity(4);
+    loop {
+        let buffer = reader.fill_buf().map_err(|e| ParseError::ReadError(e.to_string()))?;
+        if buffer.is_empty() {
+            break;
+        }
+        for &byte in buffer {
+            partial.push(byte);
+            let c = match std::str::from_utf8(&partial) {
+                Ok(c) => c.chars().next().unwrap_or_default(),
+                Err(e) if e.error_len().is_none() => continue,
+                Err(_) => return Err(ParseError::InvalidUtf8),
+            };
+            partial.clear();
+            match c {
+                '\n' => {
+                    if battlefields == Battlefields::Independent {
+                        totals.push(tally.finish());
+                    }
+                    line_empty = true;
+                },
+                '\r' => (),
+                c => {
+                    tally.push(catalogue.creature(c)?);
+                    line_empty = false;
+                },
+            }
+        }
+        let len = buffer.len();
+        reader.consume(len);
+    }
+    if !partial.is_empty() {
+        return Err(ParseError::InvalidUtf8);
+    }
+    // Like `str::lines()`, we don't count an empty last line, unless all lines go together.
+    if battlefields == Battlefields::Concatenated || !line_empty {
+        totals.push(tally.finish());
+    }
+    Ok(totals)
+}
+
 pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
-    let items: Vec<_> = input.lines().next().unwrap_or_default().chars().map(Creature::try_from).collect::<Result<Vec<_>, _>>()?;
+    run_with_catalogue(input, part, &Catalogue::default())
+}
+
+/// Like `run()`, but under the rules of the given `catalogue` instead of the default ones.
+pub fn run_with_catalogue(input: &str, part: usize, catalogue: &Catalogue) -> Result<usize, ParseError> {
     match part {
-        1 => Ok(items.iter().map(|c| c.required_potions()).sum()),
-        2 => Ok(items.chunks(2).map(required_potions_for_group).sum()),
-        3 => Ok(items.chunks(3).map(required_potions_for_group).sum()),
+        1..=3 => Ok(battles(input, part, catalogue, Battlefields::Independent)?.iter().map(|battle| battle.total).sum()),
         _ => panic!("Illegal part number"),
     }
 }
@@ -67,7 +427,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -87,4 +447,114 @@ mod tests {
             assert_eq!(run(&challenge_input, part), Ok(expected[part-1]));
         }
     }
+
+    #[test]
+    fn test_catalogue() {
+        let default = Catalogue::try_from("creatures: A=0 B=1 C=3 D=5\nempty: x\nbonus: 2=2 3=6");
+        assert_eq!(default, Ok(Catalogue::default()));
+
+        // Ants need a potion, too, there are no Dragonflies, and empty spots are marked with '.'.
+        let variant = Catalogue::try_from("creatures: A=1 B=1 C=3\nempty: .\nbonus: 3=1").unwrap();
+        assert_eq!(run_with_catalogue("AB.CCA.", 1, &variant), Ok(9));
+        assert_eq!(run_with_catalogue("AB.CCA.", 3, &variant), Ok(10));
+        assert_eq!(run_with_catalogue("ABxD", 1, &variant), Err(ParseError::ParseCharError('x')));
+
+        // Left out lines keep the defaults, given ones replace them.
+        let bonus_only = Catalogue::try_from("bonus: 2=2").unwrap();
+        assert_eq!(bonus_only.creature('D'), Ok(Creature::Dragonfly));
+        assert_eq!((bonus_only.group_bonus(2), bonus_only.group_bonus(3)), (2, 0));
+        let no_dragonflies = Catalogue::try_from("creatures: A=0\ncreatures: B=1").unwrap();
+        assert_eq!(no_dragonflies.creature('B'), Ok(Creature::Beetle));
+        assert_eq!(no_dragonflies.creature('D'), Err(ParseError::ParseCharError('D')));
+        assert_eq!(no_dragonflies.creature('x'), Ok(Creature::None));
+        assert_eq!(Catalogue::try_from(""), Ok(Catalogue::default()));
+        assert_eq!(Catalogue::try_from("bonus:").unwrap().group_bonus(2), 0);
+        let group = ['A', 'x', 'D'].map(|c| Creature::try_from(c).unwrap());
+        assert_eq!(group, [Creature::Ant, Creature::None, Creature::Dragonfly]);
+        assert_eq!(required_potions_for_group(&group), 7);
+        assert_eq!(required_potions_for_group(&group), Catalogue::default().required_potions_for_group(&group));
+
+        // Creatures are the same, no matter how many potions a catalogue prices them at.
+        let variant = Catalogue::try_from("creatures: A=1 E=2").unwrap();
+        assert_eq!(variant.creature('A'), Ok(Creature::Ant));
+        assert_eq!(variant.creature('E'), Ok(Creature::Other('E')));
+        assert_eq!(Creature::Other('A'), Creature::Ant);
+        assert_eq!((variant.potions(Creature::Ant), variant.potions(Creature::Beetle)), (1, 0));
+
+        assert_eq!(Catalogue::try_from("empty: xy"), Err(ParseError::ConfigLineMalformed("empty: xy".to_string())));
+        assert!(matches!(Catalogue::try_from("bonus: 2=many"), Err(ParseError::ParseIntError(_))));
+    }
+
+    #[test]
+    fn test_battle() {
+        let catalogue = Catalogue::default();
+        let creatures: Vec<_> = "xBxAAABCDxCC".chars().map(|c| catalogue.creature(c)).collect::<Result<_, _>>().unwrap();
+        assert_eq!(battle(&creatures, 3, &catalogue, |count| catalogue.group_bonus(count)), Battle { total: 30, groups: vec![1, 6, 15, 8] });
+
+        // Knights fighting in fours, where every creature needs one extra potion for each other
+        // creature in its group.
+        assert_eq!(battle(&creatures, 4, &catalogue, |count| count * count.saturating_sub(1)), Battle { total: 36, groups: vec![3, 16, 17] });
+    }
+
+    // All the ways to split `creatures` into consecutive groups of at most `max_group_size`.
+    fn partitions(creatures: &[Creature], max_group_size: usize) -> Vec<Vec<&[Creature]>> {
+        if creatures.is_empty() {
+            return vec![Vec::new()];
+        }
+        (1..=max_group_size.min(creatures.len())).flat_map(|len| {
+            partitions(&creatures[len..], max_group_size).into_iter().map(move |mut rest| {
+                rest.insert(0, &creatures[..len]);
+                rest
+            })
+        }).collect()
+    }
+
+    #[test]
+    fn test_partition() {
+        let variants = [
+            Catalogue::default(),
+            // Lone creatures are expensive, and pairs are the cheapest.
+            Catalogue::try_from("creatures: A=0 B=1 C=3 D=5\nempty: x\nbonus: 1=4 2=1 3=3 4=8").unwrap(),
+        ];
+        for catalogue in variants {
+            let creatures: Vec<_> = "xBxAAABCDxCC".chars().map(|c| catalogue.creature(c)).collect::<Result<_, _>>().unwrap();
+            for max_group_size in 1..=4 {
+                let totals: Vec<usize> = partitions(&creatures, max_group_size)
+                    .iter()
+                    .map(|groups| groups.iter().map(|group| catalogue.required_potions_for_group(group)).sum())
+                    .collect();
+                for (partition, expected) in [
+                    (min_potions_partition(&creatures, max_group_size, &catalogue), totals.iter().min()),
+                    (max_potions_partition(&creatures, max_group_size, &catalogue), totals.iter().max()),
+                ] {
+                    assert_eq!(Some(&partition.total), expected);
+                    assert_eq!(partition.groups.concat(), creatures);
+                    assert!(partition.groups.iter().all(|group| !group.is_empty() && group.len() <= max_group_size));
+                    assert_eq!(partition.groups.iter().map(|group| catalogue.required_potions_for_group(group)).sum::<usize>(), partition.total);
+                }
+            }
+        }
+        assert_eq!(min_potions_partition(&[], 3, &Catalogue::default()), Partition { total: 0, groups: Vec::new() });
+    }
+
+    #[test]
+    fn test_battlefields() {
+        let catalogue = Catalogue::default();
+        let notes = "xBxAAABCDxCC\nAxBCDDCAxD\n\nABBAC";
+        let independent: Vec<_> = battles(notes, 2, &catalogue, Battlefields::Independent).unwrap().iter().map(|battle| battle.total).collect();
+        assert_eq!(independent, [22, 28, 0, 9]);
+        let concatenated = battles(notes, 2, &catalogue, Battlefields::Concatenated).unwrap();
+        assert_eq!(concatenated.len(), 1);
+        assert_eq!(concatenated[0].total, 59);
+
+        // Read in tiny chunks to make sure groups and lines may span several reads.
+        let reader = std::io::BufReader::with_capacity(3, notes.as_bytes());
+        assert_eq!(battle_totals(reader, 2, &catalogue, Battlefields::Independent), Ok(independent));
+        let reader = std::io::BufReader::with_capacity(3, notes.as_bytes());
+        assert_eq!(battle_totals(reader, 2, &catalogue, Battlefields::Concatenated), Ok(vec![59]));
+
+        assert_eq!(battle_totals("AB\r\nC\n".as_bytes(), 2, &catalogue, Battlefields::Independent), Ok(vec![3, 3]));
+        assert_eq!(battle_totals("AB\nxÄ".as_bytes(), 2, &catalogue, Battlefields::Independent), Err(ParseError::ParseCharError('Ä')));
+        assert_eq!(battle_totals(&b"AB\xff"[..], 2, &catalogue, Battlefields::Independent), Err(ParseError::InvalidUtf8));
+    }
 }
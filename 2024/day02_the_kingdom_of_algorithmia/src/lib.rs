@@ -1,6 +1,8 @@
 use core::fmt::Display;
+use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError<'a> {
     LineMalformed(&'a str),
     NoteMalformed(&'a str),
@@ -19,63 +21,458 @@ fn reverse_str(s: &str) -> String {
     s.chars().rev().collect::<String>()
 }
 
-fn find_matches(word: &str, runic: &[String]) -> Vec<bool> {
-    let mut matches = vec![false; word.len()];
-    runic.iter().for_each(|r| {
-        let mut start = 0;
-        while let Some(match_idx) = word[start..].find(r) {
-            (start+match_idx..start+match_idx+r.len()).for_each(|idx| matches[idx] = true);
-            start += match_idx + 1;
+/// An Aho-Corasick automaton over a set of patterns, which finds all their (possibly overlapping)
+/// occurences in a text in a single pass. Patterns and texts are compared `char` by `char`.
+pub struct Automaton {
+    // The trie of all patterns. For each node, we store its children, the node of its longest
+    // proper suffix in the trie (where to continue if no child matches), and the length of the
+    // longest pattern that is a suffix of it. We also keep the pattern ending exactly at each
+    // node (if any), and the node of its next shorter suffix that is a pattern (or 0).
+    children: Vec<HashMap<char, usize>>,
+    fallback: Vec<usize>,
+    longest_match: Vec<usize>,
+    pattern_at: Vec<Option<usize>>,
+    next_pattern: Vec<usize>,
+    pattern_len: Vec<usize>,
+    max_len: usize,
+}
+
+/// An occurence of a pattern: Its index in the list of patterns the `Automaton` was built from,
+/// and the positions of its first symbol and the one after its last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Occurence {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Automaton {
+    pub fn new(patterns: &[impl AsRef<str>]) -> Self {
+        let mut children = vec![HashMap::new()];
+        let mut longest_match = vec![0];
+        let mut pattern_at = vec![None];
+        let mut pattern_len = Vec::new();
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let mut node = 0;
+            let mut len = 0;
+            for symbol in pattern.as_ref().chars() {
+                len += 1;
+                node = match children[node].get(&symbol) {
+                    Some(&child) => child,
+                    None => {
+                        let child = children.len();
+                        children.push(HashMap::new());
+                        longest_match.push(0);
+                        pattern_at.push(None);
+                        children[node].insert(symbol, child);
+                        child
+                    },
+                };
+            }
+            longest_match[node] = longest_match[node].max(len);
+            // Patterns occuring more than once (e. g. palindromes in both directions) are
+            // reported as the first of them only. Empty ones are never reported.
+            if node != 0 && pattern_at[node].is_none() {
+                pattern_at[node] = Some(idx);
+            }
+            pattern_len.push(len);
+        }
+
+        // Breadth first, so the fallback of every node (which is less deep) is known before we
+        // reach its children.
+        let mut fallback = vec![0; children.len()];
+        let mut next_pattern = vec![0; children.len()];
+        let mut open_set: VecDeque<usize> = children[0].values().copied().collect();
+        while let Some(node) = open_set.pop_front() {
+            for (&symbol, &child) in &children[node] {
+                let mut candidate = fallback[node];
+                while candidate != 0 && !children[candidate].contains_key(&symbol) {
+                    candidate = fallback[candidate];
+                }
+                fallback[child] = children[candidate].get(&symbol).copied().unwrap_or(0);
+                longest_match[child] = longest_match[child].max(longest_match[fallback[child]]);
+                next_pattern[child] = if pattern_at[fallback[child]].is_some() { fallback[child] } else { next_pattern[fallback[child]] };
+                open_set.push_back(child);
+            }
+        }
+        let max_len = longest_match.iter().max().copied().unwrap_or(0);
+        Self { children, fallback, longest_match, pattern_at, next_pattern, pattern_len, max_len }
+    }
+
+    fn step(&self, mut node: usize, symbol: char) -> usize {
+        loop {
+            if let Some(&child) = self.children[node].get(&symbol) {
+                return child;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.fallback[node];
+        }
+    }
+
+    /// All occurences of any pattern in `symbols`, ordered by their ends.
+    pub fn occurences_in(&self, symbols: impl IntoIterator<Item = char>) -> Vec<Occurence> {
+        self.occurences_with_gaps(symbols.into_iter().map(Some))
+    }
+
+    // Like `occurences_in()`, but no pattern can match across a `None`.
+    fn occurences_with_gaps(&self, symbols: impl IntoIterator<Item = Option<char>>) -> Vec<Occurence> {
+        let mut node = 0;
+        let mut occurences = Vec::new();
+        for (idx, symbol) in symbols.into_iter().enumerate() {
+            node = symbol.map(|symbol| self.step(node, symbol)).unwrap_or(0);
+            let mut suffix = if self.pattern_at[node].is_some() { node } else { self.next_pattern[node] };
+            while let Some(pattern) = self.pattern_at[suffix] {
+                occurences.push(Occurence { pattern, start: idx + 1 - self.pattern_len[pattern], end: idx + 1 });
+                suffix = self.next_pattern[suffix];
+            }
+        }
+        occurences
+    }
+
+    /// Returns a `Vec` with one entry per `char` of `text`, which is `true` iff that `char` is
+    /// part of any occurence of a pattern.
+    pub fn find_matches(&self, text: &str) -> Vec<bool> {
+        self.find_matches_in(text.chars())
+    }
+
+    /// Like `find_matches()`, but for any sequence of symbols, e. g. a column of a grid.
+    pub fn find_matches_in(&self, symbols: impl IntoIterator<Item = char>) -> Vec<bool> {
+        self.find_matches_with_gaps(symbols.into_iter().map(Some))
+    }
+
+    // Like `find_matches_in()`, but no pattern can match across a `None`.
+    fn find_matches_with_gaps(&self, symbols: impl IntoIterator<Item = Option<char>>) -> Vec<bool> {
+        let mut node = 0;
+        let longest_ending_at: Vec<usize> = symbols.into_iter().map(|symbol| {
+            node = symbol.map(|symbol| self.step(node, symbol)).unwrap_or(0);
+            self.longest_match[node]
+        }).collect();
+
+        // Going backwards, keep track of the earliest start of any match ending here or later. A
+        // symbol is covered iff that is at or before it.
+        let mut earliest_start = usize::MAX;
+        let mut matches = vec![false; longest_ending_at.len()];
+        for (idx, &len) in longest_ending_at.iter().enumerate().rev() {
+            if len > 0 {
+                earliest_start = earliest_start.min(idx + 1 - len);
+            }
+            matches[idx] = earliest_start <= idx;
+        }
+        matches
+    }
+}
+
+/// Returns a `Vec` with one entry per `char` of `word`, which is `true` iff that `char` is part
+/// of any of the `runic` words. To search for the same words repeatedly, build an `Automaton` once
+/// instead.
+pub fn find_matches(word: &str, runic: &[String]) -> Vec<bool> {
+    Automaton::new(runic).find_matches(word)
+}
+
+/// Which edges of the armour scales wrap around to the opposite side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology { Plane, HorizontalCylinder, VerticalCylinder, Torus }
+
+/// The directions words may be read in on the armour scales (in addition to their reverses).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Directions { Horizontal, Orthogonal, WithDiagonals }
+
+impl Directions {
+    fn steps(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Horizontal => &[(1, 0)],
+            Self::Orthogonal => &[(1, 0), (0, 1)],
+            Self::WithDiagonals => &[(1, 0), (0, 1), (1, 1), (1, -1)],
+        }
+    }
+}
+
+impl Topology {
+    /// The cell reached from `(x, y)` by one `step`, if it's still on the scales.
+    fn neighbour(self, (x, y): (usize, usize), (dx, dy): (isize, isize), width: usize, height: usize) -> Option<(usize, usize)> {
+        let (wrap_x, wrap_y) = match self {
+            Self::Plane => (false, false),
+            Self::HorizontalCylinder => (true, false),
+            Self::VerticalCylinder => (false, true),
+            Self::Torus => (true, true),
+        };
+        let coordinate = |pos: usize, delta: isize, len: usize, wrap: bool| {
+            let next = pos as isize + delta;
+            if wrap {
+                Some(next.rem_euclid(len as isize) as usize)
+            } else {
+                (0..len as isize).contains(&next).then_some(next as usize)
+            }
+        };
+        Some((coordinate(x, dx, width, wrap_x)?, coordinate(y, dy, height, wrap_y)?))
+    }
+
+    /// All lines of cells in direction `step`, and whether each of them loops back onto itself.
+    fn lines(self, step: (isize, isize), width: usize, height: usize) -> Vec<(Vec<(usize, usize)>, bool)> {
+        let mut visited = vec![vec![false; width]; height];
+        let mut lines = Vec::new();
+        let follow = |start: (usize, usize), visited: &mut [Vec<bool>]| {
+            let mut line = vec![start];
+            visited[start.1][start.0] = true;
+            let mut current = start;
+            while let Some(next) = self.neighbour(current, step, width, height) {
+                if next == start {
+                    return (line, true);
+                }
+                line.push(next);
+                visited[next.1][next.0] = true;
+                current = next;
+            }
+            (line, false)
+        };
+        // Lines with an end start at a cell without a predecessor. All other cells lie on loops.
+        let backwards = (-step.0, -step.1);
+        for y in 0..height {
+            for x in 0..width {
+                if self.neighbour((x, y), backwards, width, height).is_none() {
+                    lines.push(follow((x, y), &mut visited));
+                }
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                if !visited[y][x] {
+                    lines.push(follow((x, y), &mut visited));
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// An occurence of a runic word (as its index in the list of words) in the inscription, and the
+/// cells `(x, y)` it covers, in the order of the line it was found on. `x` is the column (in
+/// `char`s, counting spaces) and `y` the line of the inscription, both starting at 0.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct WordMatch {
+    pub word: usize,
+    pub reversed: bool,
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// The result of a search: Which cells are part of a runic word, and which words were found.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Matches {
+    mask: Vec<Vec<bool>>,
+    words: Vec<WordMatch>,
+}
+
+/// How the `Matches` are marked when rendering the inscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    /// Bold red, using ANSI escape codes.
+    Ansi,
+    /// Surrounded by square brackets.
+    Brackets,
+}
+
+impl Matches {
+    /// One row per line of the inscription, with one entry per symbol (including spaces), which
+    /// is `true` iff it's part of any runic word.
+    pub fn mask(&self) -> &[Vec<bool>] {
+        &self.mask
+    }
+
+    /// All occurences of runic words, ordered by word, then by the cells they cover.
+    pub fn words(&self) -> &[WordMatch] {
+        &self.words
+    }
+
+    /// The number of matched cells.
+    pub fn count(&self) -> usize {
+        count_cells(&self.mask)
+    }
+
+    /// The coordinates `(x, y)` of the matched cells, row by row.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        self.mask
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(|(_x, m)| **m).map(move |(x, _m)| (x, y)))
+            .collect()
+    }
+}
+
+// Like `search()`, but only finds which cells are part of a runic word, not the words themselves.
+fn search_mask(rows: &[&str], runic: &Automaton, topology: Topology, directions: Directions) -> Vec<Vec<bool>> {
+    let grid: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let height = grid.len();
+    let mut mask = vec![vec![false; width]; height];
+
+    for &step in directions.steps() {
+        for (line, is_loop) in topology.lines(step, width, height) {
+            // As in `search()`, but every cell covered after the first lap is also covered on it
+            // by the same match, so we just wrap all of them around.
+            let len = if is_loop { line.len() + runic.max_len.saturating_sub(1) } else { line.len() };
+            let symbols = line.iter().cycle().take(len).map(|&(x, y)| grid[y].get(x).copied());
+            runic.find_matches_with_gaps(symbols)
+                .iter()
+                .enumerate()
+                .filter(|(_idx, matched)| **matched)
+                .for_each(|(idx, _matched)| {
+                    let (x, y) = line[idx % line.len()];
+                    mask[y][x] = true;
+                });
         }
-    });
-    matches
+    }
+    mask
 }
 
-fn count_symbols(word: &str, runic: &[String]) -> usize {
-    find_matches(word, runic).iter().filter(|i| **i).count()
+fn count_cells(mask: &[Vec<bool>]) -> usize {
+    mask.iter().map(|row| row.iter().filter(|m| **m).count()).sum()
 }
 
-fn count_symbols_wrapping(words: &[&str], runic: &[String]) -> usize {
-    let width = words[0].len();
-    let height = words.len();
-    let mut matches = vec![vec![false; width]; height];
+fn search(rows: &[&str], runic: &Automaton, topology: Topology, directions: Directions) -> Matches {
+    let grid: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    // Rows should all be of the same length. Cells missing from shorter ones never match.
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let height = grid.len();
+    let mut mask = vec![vec![false; width]; height];
+    let mut words = Vec::new();
+
+    for &step in directions.steps() {
+        for (line, is_loop) in topology.lines(step, width, height) {
+            // To find words crossing the seam of a loop (possibly several times), we go around
+            // until every match starting on the first lap has ended.
+            let len = if is_loop { line.len() + runic.max_len.saturating_sub(1) } else { line.len() };
+            let symbols = line.iter().cycle().take(len).map(|&(x, y)| grid[y].get(x).copied());
+            // Occurences starting after the first lap are repetitions of earlier ones.
+            for occurence in runic.occurences_with_gaps(symbols).iter().filter(|o| o.start < line.len()) {
+                let cells: Vec<_> = (occurence.start..occurence.end).map(|idx| line[idx % line.len()]).collect();
+                cells.iter().for_each(|&(x, y)| mask[y][x] = true);
+                // Our patterns are the runic words, each followed by its reverse.
+                let reversed = occurence.pattern % 2 == 1;
+                words.push(WordMatch { word: occurence.pattern / 2, reversed, cells });
+            }
+        }
+    }
+    // Words of a single symbol are found in every direction.
+    words.sort();
+    words.dedup();
+    Matches { mask, words }
+}
 
-    // horizontal
-    words.iter().enumerate().for_each(|(y, w)| {
-        let word = format!("{w}{w}{w}");
-        find_matches(&word, runic).iter().enumerate().skip(width).take(width).for_each(|(x, m)| if *m { matches[y][x-width] = true });
-    });
+/// The parsed notes: A list of runic words and the inscription they are to be searched in, line
+/// by line as written.
+pub struct Note<'a> {
+    words: Vec<&'a str>,
+    words_omni: Automaton,
+    inscription: Vec<&'a str>,
+}
 
-    // vertical
-    (0..width).for_each(|x| {
-        let word = words.iter().map(|w| w.chars().nth(x).unwrap()).collect::<String>();
-        find_matches(&word, runic).iter().enumerate().for_each(|(y, m)| if *m { matches[y][x] = true });
-    });
+impl<'a> TryFrom<&'a str> for Note<'a> {
+    type Error = ParseError<'a>;
 
-    matches.iter().map(|line| line.iter().filter(|m| **m).count()).sum()
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let lines: Vec<_> = value.lines().collect::<Vec<_>>();
+        if lines.len() < 3 {
+            return Err(Self::Error::NoteMalformed(value));
+        }
+        if let Some((_, words)) = lines[0].split_once(':') {
+            let words = words.split(',').collect::<Vec<_>>();
+            let mut words_omni = Vec::new();
+            words.iter().for_each(|w| {
+                words_omni.push(w.to_string());
+                words_omni.push(reverse_str(w));
+            });
+            let inscription = lines[2..].to_vec();
+            Ok(Self { words, words_omni: Automaton::new(&words_omni), inscription })
+        } else {
+            Err(Self::Error::LineMalformed(lines[0]))
+        }
+    }
 }
 
-pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
-    let lines: Vec<_> = input.lines().collect::<Vec<_>>();
-    if lines.len() < 3 {
-        return Err(ParseError::NoteMalformed(input));
+impl Note<'_> {
+    pub fn words(&self) -> &[&str] {
+        &self.words
     }
-    if let Some((_, words)) = lines[0].split_once(':') {
-        let words = words.split(',').collect::<Vec<_>>();
-        let mut words_omni = Vec::new();
-        words.iter().for_each(|w| {
-            words_omni.push(w.to_string());
-            words_omni.push(reverse_str(w));
-        });
-        let inscription = lines.iter().skip(2).flat_map(|l| l.split_whitespace().collect::<Vec<_>>()).collect::<Vec<_>>();
-        match part {
-            1 => Ok(inscription.iter().map(|w| words.iter().map(|word| w.matches(*word).count()).sum::<usize>()).sum()),
-            2 => Ok(inscription.iter().map(|w| count_symbols(w, &words_omni)).sum()),
-            3 => Ok(count_symbols_wrapping(&inscription, &words_omni)),
-            _ => panic!("Illegal part number"),
+
+    /// The lines of the inscription, including any spaces.
+    pub fn inscription(&self) -> &[&str] {
+        &self.inscription
+    }
+
+    /// The number of occurences of runic words in the inscription, read left to right only.
+    /// Occurences of the same word are counted without overlaps (like `str::matches()`), but
+    /// those of different words may overlap.
+    pub fn count_words(&self) -> usize {
+        self.inscription.iter().map(|w| self.words.iter().map(|word| w.matches(*word).count()).sum::<usize>()).sum()
+    }
+
+    /// The number of symbols in the inscription, that are part of a runic word read in either
+    /// direction.
+    pub fn count_symbols(&self) -> usize {
+        count_cells(&search_mask(&self.inscription, &self.words_omni, Topology::Plane, Directions::Horizontal))
+    }
+
+    /// Find the symbols that are part of a runic word read in either direction, in each line of
+    /// the inscription (which are the rows of the result). Words never span a space.
+    pub fn search_inscription(&self) -> Matches {
+        search(&self.inscription, &self.words_omni, Topology::Plane, Directions::Horizontal)
+    }
+
+    /// Like `count_symbols()`, but treating the inscription as a grid of armour scales, where
+    /// words can also be read vertically and wrap around horizontally.
+    pub fn count_symbols_wrapping(&self) -> usize {
+        count_cells(&search_mask(&self.inscription, &self.words_omni, Topology::HorizontalCylinder, Directions::Orthogonal))
+    }
+
+    /// Find the symbols that are part of a runic word, treating the inscription as a grid of
+    /// armour scales with the given `topology`, and reading words in the given `directions`.
+    /// Spaces are scales without a symbol, which are never part of a word.
+    pub fn search_scales(&self, topology: Topology, directions: Directions) -> Matches {
+        search(&self.inscription, &self.words_omni, topology, directions)
+    }
+
+    /// The inscription as written, with the symbols in `matches` highlighted, followed by a list
+    /// of the words found and the cells `(x, y)` they cover.
+    pub fn render(&self, matches: &Matches, highlight: Highlight) -> String {
+        let (open, close) = match highlight {
+            Highlight::Ansi => ("\x1b[1;31m", "\x1b[0m"),
+            Highlight::Brackets => ("[", "]"),
+        };
+        let mut res = String::new();
+        for (row, mask) in self.inscription.iter().zip(matches.mask.iter()) {
+            let mut highlighted = false;
+            for (symbol, &matched) in row.chars().zip(mask.iter()) {
+                if matched != highlighted {
+                    res += if matched { open } else { close };
+                    highlighted = matched;
+                }
+                res.push(symbol);
+            }
+            if highlighted {
+                res += close;
+            }
+            res.push('\n');
         }
-    } else {
-        Err(ParseError::LineMalformed(lines[0]))
+        for word in &matches.words {
+            let cells = word.cells.iter().map(|(x, y)| format!("({x}, {y})")).collect::<Vec<_>>().join(" ");
+            let reversed = if word.reversed { " (reversed)" } else { "" };
+            res += &format!("\n{}{reversed}: {cells}", self.words[word.word]);
+        }
+        res
+    }
+}
+
+pub fn run(input: &str, part: usize) -> Result<usize, ParseError<'_>> {
+    let note = Note::try_from(input)?;
+    match part {
+        1 => Ok(note.count_words()),
+        2 => Ok(note.count_symbols()),
+        3 => Ok(note.count_symbols_wrapping()),
+        _ => panic!("Illegal part number"),
     }
 }
 
@@ -85,7 +482,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -97,6 +494,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_matches() {
+        let runic = ["THE", "OWE", "MES", "ROD", "RODEO", "HER"].map(String::from);
+        assert_eq!(find_matches("XTHEREX", &runic), [false, true, true, true, true, false, false]);
+        assert_eq!(find_matches("PRODEOX", &runic), [false, true, true, true, true, true, false]);
+        assert_eq!(find_matches("HEROWE", &runic), [true; 6]);
+        assert_eq!(find_matches("TH", &runic), [false; 2]);
+        assert_eq!(find_matches("", &runic), []);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let runic = ["ΘΕΑ", "ΑΕΘ", "ΛΟΓΟΣ", "ΣΟΓΟΛ"].map(String::from);
+        assert_eq!(find_matches("ΟΘΕΑΛΟΓΟΣ", &runic), [false, true, true, true, true, true, true, true, true]);
+        assert_eq!(find_matches("ÄΘΕΑ", &runic), [false, true, true, true]);
+
+        let note = "WORDS:МИР,ДОМ,日本\n\nПРИВЕТ ДОМОВОЙ МИРИМ 本日本語";
+        assert_eq!(run(note, 1), Ok(3));
+        assert_eq!(run(note, 2), Ok(3 + 5 + 3));
+
+        // МИР wraps around the end of the first row, ДОМ runs down the third column.
+        let scales = "WORDS:МИР,ДОМ\n\nИРДМ\nЯЯОЯ\nБЁМЖ";
+        assert_eq!(run(scales, 3), Ok(6));
+    }
+
+    #[test]
+    fn test_topology() {
+        // ABC wraps around the first row, XY around the third column.
+        let note = Note::try_from("WORDS:ABC,XY\n\nBCYA\nQQQQ\nQQXQ").unwrap();
+        let search = |topology| note.search_scales(topology, Directions::Orthogonal).cells();
+        assert_eq!(search(Topology::Plane), []);
+        assert_eq!(search(Topology::HorizontalCylinder), [(0, 0), (1, 0), (3, 0)]);
+        assert_eq!(search(Topology::VerticalCylinder), [(2, 0), (2, 2)]);
+        assert_eq!(search(Topology::Torus), [(0, 0), (1, 0), (2, 0), (3, 0), (2, 2)]);
+        assert_eq!(note.search_scales(Topology::Torus, Directions::Orthogonal).count(), 5);
+        for topology in [Topology::Plane, Topology::HorizontalCylinder, Topology::VerticalCylinder, Topology::Torus] {
+            assert_eq!(search_mask(note.inscription(), &note.words_omni, topology, Directions::WithDiagonals), note.search_scales(topology, Directions::WithDiagonals).mask());
+        }
+
+        let search = |scales: &str, topology, directions| Note::try_from(scales).unwrap().search_scales(topology, directions).cells();
+        assert_eq!(search("WORDS:ABC\n\nAQQ\nQBQ\nQQC", Topology::Plane, Directions::Orthogonal), []);
+        assert_eq!(search("WORDS:ABC\n\nAQQ\nQBQ\nQQC", Topology::Plane, Directions::WithDiagonals), [(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(search("WORDS:ABC\n\nQQA\nQBQ\nCQQ", Topology::Plane, Directions::WithDiagonals), [(2, 0), (1, 1), (0, 2)]);
+        assert_eq!(search("WORDS:ABC\n\nBQQ\nQCQ\nQQA", Topology::Plane, Directions::WithDiagonals), []);
+        assert_eq!(search("WORDS:ABC\n\nBQQ\nQCQ\nQQA", Topology::Torus, Directions::WithDiagonals), [(0, 0), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_render() {
+        let note = Note::try_from("WORDS:THE,OWE\n\nTHE POWER\nEHT").unwrap();
+        let matches = note.search_inscription();
+        assert_eq!(matches.mask()[0], [true, true, true, false, false, true, true, true, false]);
+        assert_eq!(matches.words()[1], WordMatch { word: 0, reversed: true, cells: vec![(0, 1), (1, 1), (2, 1)] });
+        assert_eq!(note.render(&matches, Highlight::Brackets), "[THE] P[OWE]R\n[EHT]\n
+THE: (0, 0) (1, 0) (2, 0)
+THE (reversed): (0, 1) (1, 1) (2, 1)
+OWE: (5, 0) (6, 0) (7, 0)");
+        assert!(note.render(&matches, Highlight::Ansi).starts_with("\x1b[1;31mTHE\x1b[0m P\x1b[1;31mOWE\x1b[0mR\n"));
+
+        // Words don't span spaces, and the spaces are kept as written.
+        let note = Note::try_from("WORDS:THE\n\nT HE  THE").unwrap();
+        assert_eq!(note.render(&note.search_inscription(), Highlight::Brackets), "T HE  [THE]\n\nTHE: (6, 0) (7, 0) (8, 0)");
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [34, 5078, 11593];
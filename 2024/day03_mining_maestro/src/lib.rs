@@ -1,22 +1,28 @@
 use core::fmt::Display;
+use std::collections::VecDeque;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
-    GridMalformed(usize, usize, usize),
     InvalidTile(char),
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::GridMalformed(first, idx, len) => write!(f, "Input Grid is not rectangular. First line has {first} characters, but line {idx} has {len}."),
             Self::InvalidTile(e) => write!(f, "Unable to parse into a tile: {e}"),
         }
     }
 }
 
-struct Map {
-    map: Vec<Vec<usize>>,
+/// The mining grid, as a stack of slices separated by empty lines in the input. After parsing,
+/// each cell holds 1 for earth (`#`) and 0 for ground level (`.`). After `maximize()`, each cell
+/// holds the depth to which it can be dug. Lines and slices may be of different lengths, with any
+/// cells missing from the shorter ones (just like those outside the map) at ground level.
+#[derive(Clone)]
+pub struct Map {
+    map: Vec<Vec<Vec<usize>>>,
+    slices: usize,
     height: usize,
     width: usize,
 }
@@ -25,83 +31,203 @@ impl TryFrom<&str> for Map {
     type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let lines: Vec<_> = value.lines().collect();
-        if lines.is_empty() {
-            return Ok(Self { map: vec![vec![]], height: 0, width: 0, });
-        }
-        let width = lines[0].len();
-        let mut map = vec![vec![0; width]; lines.len()];
-        for (y, l) in lines.iter().enumerate() {
-            if l.len() != width {
-                return Err(Self::Error::GridMalformed(width, y, l.len()));
+        let mut slices = vec![Vec::new()];
+        for line in value.lines() {
+            match (line.trim().is_empty(), slices.last_mut()) {
+                (false, Some(slice)) => slice.push(line),
+                (true, Some(slice)) if !slice.is_empty() => slices.push(Vec::new()),
+                _ => (),
             }
-            for (x, c) in l.chars().enumerate() {
-                match c {
-                    '.' => (),
-                    '#' => map[y][x] = 1,
-                    e => return Err(Self::Error::InvalidTile(e)),
+        }
+        if slices.len() > 1 && slices.last().is_some_and(Vec::is_empty) {
+            slices.pop();
+        }
+        let height = slices.iter().map(Vec::len).max().unwrap_or(0);
+        let width = slices.iter().flatten().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut map = vec![vec![vec![0; width]; height]; slices.len()];
+        for (z, lines) in slices.iter().enumerate() {
+            for (y, l) in lines.iter().enumerate() {
+                for (x, c) in l.chars().enumerate() {
+                    match c {
+                        '.' => (),
+                        '#' => map[z][y][x] = 1,
+                        e => return Err(Self::Error::InvalidTile(e)),
+                    }
                 }
             }
         }
-        Ok(Self { map, height: lines.len(), width })
+        Ok(Self { map, slices: slices.len(), height, width })
     }
 }
 
 impl Map{
-    fn at_or_zero(&self, (x, y): (usize, usize)) -> usize {
-        if x >= self.width || y >= self.height { 0 } else { self.map[y][x] }
-    }
-
-    fn neighbours(&self, (x, y): (usize, usize), royal: bool) -> Vec<usize> {
-        let mut res = if royal {
-            vec![
-                self.at_or_zero((x.wrapping_sub(1), y)),
-                self.at_or_zero((x+1, y)),
-                self.at_or_zero((x, y.wrapping_sub(1))),
-                self.at_or_zero((x, y+1)),
-                self.at_or_zero((x.wrapping_sub(1), y.wrapping_sub(1))),
-                self.at_or_zero((x+1, y.wrapping_sub(1))),
-                self.at_or_zero((x.wrapping_sub(1), y+1)),
-                self.at_or_zero((x+1, y+1)),
-            ]
-        } else {
-            vec![
-                self.at_or_zero((x.wrapping_sub(1), y)),
-                self.at_or_zero((x+1, y)),
-                self.at_or_zero((x, y.wrapping_sub(1))),
-                self.at_or_zero((x, y+1)),
-            ]
-        };
-        res.sort();
-        res
-    }
+    /// Dig each cell as deep as possible, such that no two neighbouring cells differ by more
+    /// than 1 in depth. Cells are neighbours if they share a face (i. e. 4 neighbours per cell in
+    /// a single slice, or 6 between several slices). If `royal` is set, cells sharing only an
+    /// edge or corner are neighbours, too (8 or 26).
+    ///
+    /// The depth of each cell is its distance to the closest cell at ground level (with
+    /// everything outside the map being at ground level), so we find them all in a single
+    /// breadth first search from there. A map consisting of a single slice is treated as 2D,
+    /// i. e. there is nothing above or below it.
+    pub fn maximize(&mut self, royal: bool) {
+        let dz = if self.slices > 1 { -1..=1 } else { 0..=0 };
+        let offsets: Vec<(isize, isize, isize)> = dz
+            .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))))
+            .filter(|&(dx, dy, dz)| {
+                let axes = [dx, dy, dz].iter().filter(|d| **d != 0).count();
+                axes == 1 || (royal && axes > 1)
+            }).collect();
+        let (width, height, slices) = (self.width, self.height, self.slices);
+        let neighbours = |(x, y, z): (usize, usize, usize)| offsets.iter().map(move |(dx, dy, dz)| {
+            let (x, y, z) = (x.wrapping_add_signed(*dx), y.wrapping_add_signed(*dy), z.wrapping_add_signed(*dz));
+            (x < width && y < height && z < slices).then_some((x, y, z))
+        });
 
-    fn maximize(&mut self, royal: bool) {
-        let (x_min, y_min, x_max, y_max) = if royal {
-            (0, 0, self.width, self.height)
-        } else {
-            (1, 1, self.width-1, self.height-1)
-        };
-        loop {
-            let mut changed = false;
-            (x_min..x_max).for_each(|x|
-                (y_min..y_max).for_each(|y| {
-                    let curr = self.map[y][x];
-                    let lowest = *self.neighbours((x, y), royal).first().unwrap_or(&0);
-                    if curr > 0 && lowest >= curr {
-                        self.map[y][x] = lowest + 1;
-                        changed = true;
+        let mut depths = vec![vec![vec![usize::MAX; self.width]; self.height]; self.slices];
+        let mut open_set = VecDeque::new();
+        for (z, slice) in depths.iter_mut().enumerate() {
+            for (y, row) in slice.iter_mut().enumerate() {
+                for (x, depth) in row.iter_mut().enumerate() {
+                    if self.map[z][y][x] == 0 {
+                        *depth = 0;
+                    } else if neighbours((x, y, z)).any(|n| n.is_none_or(|(x, y, z)| self.map[z][y][x] == 0)) {
+                        *depth = 1;
+                        open_set.push_back((x, y, z));
                     }
-                }));
-            if !changed {
-                return;
+                }
+            }
+        }
+        while let Some((x, y, z)) = open_set.pop_front() {
+            let depth = depths[z][y][x] + 1;
+            for (x, y, z) in neighbours((x, y, z)).flatten() {
+                if depths[z][y][x] == usize::MAX {
+                    depths[z][y][x] = depth;
+                    open_set.push_back((x, y, z));
+                }
+            }
+        }
+        self.map = depths;
+    }
+
+    /// The total number of blocks dug out.
+    pub fn total_sum(&self) -> usize {
+        self.rows().map(|line| line.iter().sum::<usize>()).sum()
+    }
+
+    /// The depth of each cell, slice by slice and row by row.
+    pub fn depths(&self) -> &[Vec<Vec<usize>>] {
+        &self.map
+    }
+
+    // All rows of all slices, top to bottom.
+    fn rows(&self) -> impl Iterator<Item = &Vec<usize>> {
+        self.map.iter().flatten()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.rows().flatten().max().copied().unwrap_or(0)
+    }
+
+    /// The depths as a text grid, one character per cell: `.` for ground level, `1` to `9`,
+    /// then `A` to `Z` for 10 to 35, and `+` for anything deeper. Slices are separated by empty
+    /// lines.
+    pub fn to_text(&self) -> String {
+        self.map.iter().map(|slice| slice.iter().map(|row| row.iter().map(|&depth| match depth {
+            0 => '.',
+            1..=35 => char::from_digit(depth as u32, 36).unwrap_or('+').to_ascii_uppercase(),
+            _ => '+',
+        }).collect::<String>()).collect::<Vec<_>>().join("\n")).collect::<Vec<_>>().join("\n\n")
+    }
+
+    /// The depths as comma separated values, one line per row. Slices are separated by empty
+    /// lines.
+    pub fn to_csv(&self) -> String {
+        self.map.iter().map(|slice| slice.iter().map(|row| row.iter().map(|depth| depth.to_string()).collect::<Vec<_>>().join(",")).collect::<Vec<_>>().join("\n")).collect::<Vec<_>>().join("\n\n")
+    }
+
+    /// The depths as a binary greyscale PGM image, where each depth is its own shade (i. e.
+    /// ground level is black and the deepest cells are white). Slices are stacked vertically.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let max = self.max_depth().clamp(1, u16::MAX as usize);
+        let mut res = format!("P5\n{} {}\n{max}\n", self.width, self.height * self.slices).into_bytes();
+        for &depth in self.rows().flatten() {
+            let depth = depth.min(max) as u16;
+            if max > u8::MAX as usize {
+                res.extend(depth.to_be_bytes());
+            } else {
+                res.push(depth as u8);
             }
         }
+        res
+    }
+
+    /// The depths as an 8 bit greyscale PNG image, scaled such that ground level is black and
+    /// the deepest cells are white. Slices are stacked vertically.
+    pub fn to_png(&self) -> Vec<u8> {
+        let max = self.max_depth().max(1);
+        let mut pixels = Vec::with_capacity((self.width + 1) * self.height * self.slices);
+        for row in self.rows() {
+            // Filter type 0 (None) for every scanline.
+            pixels.push(0);
+            pixels.extend(row.iter().map(|depth| (depth * 255 / max) as u8));
+        }
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend(((self.height * self.slices) as u32).to_be_bytes());
+        // Bit depth 8, colour type 0 (greyscale), default compression and filtering, no interlacing.
+        header.extend([8, 0, 0, 0, 0]);
+
+        let mut res = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut res, b"IHDR", &header);
+        png_chunk(&mut res, b"IDAT", &zlib_stored(&pixels));
+        png_chunk(&mut res, b"IEND", &[]);
+        res
     }
+}
 
-    fn total_sum(&self) -> usize {
-        self.map.iter().map(|line| line.iter().sum::<usize>()).sum()
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Wrap `data` into a zlib stream without actually compressing it, i. e. using only stored
+/// deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, no preset dictionary, and the check bits for this header.
+    let mut res = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        res.extend([1, 0, 0, 0xff, 0xff]);
     }
+    while let Some(block) = blocks.next() {
+        res.push(if blocks.peek().is_none() { 1 } else { 0 });
+        let len = block.len() as u16;
+        res.extend(len.to_le_bytes());
+        res.extend((!len).to_le_bytes());
+        res.extend(block);
+    }
+    res.extend(adler32(data).to_be_bytes());
+    res
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(u32::MAX, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1_u32, 0_u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
 }
 
 pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
@@ -120,7 +246,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -132,6 +258,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_export() {
+        let mut map = Map::try_from("....\n.##.\n.###\n....").unwrap();
+        map.maximize(false);
+        assert_eq!(map.to_text(), "....\n.11.\n.111\n....");
+        let mut royal = Map::try_from("#####\n#####\n#####").unwrap();
+        royal.maximize(true);
+        assert_eq!(royal.to_text(), "11111\n12221\n11111");
+        assert_eq!(royal.to_csv(), "1,1,1,1,1\n1,2,2,2,1\n1,1,1,1,1");
+        assert_eq!(royal.to_pgm(), b"P5\n5 3\n2\n\x01\x01\x01\x01\x01\x01\x02\x02\x02\x01\x01\x01\x01\x01\x01");
+
+        let png = royal.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x05\0\0\0\x03\x08\0\0\0\0"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn test_voxels() {
+        // A 3x3x3 cube of earth, except for one corner. The centre can only be reached from that
+        // corner with diagonal steps.
+        let corner = ".##\n###\n###";
+        let full = "###\n###\n###";
+        let input = format!("{corner}\n\n{full}\n\n{full}");
+        let mut map = Map::try_from(input.as_str()).unwrap();
+        map.maximize(false);
+        assert_eq!(map.depths()[1][1], [1, 2, 1]);
+        assert_eq!(map.total_sum(), 27);
+        let mut royal = Map::try_from(input.as_str()).unwrap();
+        royal.maximize(true);
+        assert_eq!(royal.total_sum(), 26);
+        assert_eq!(royal.to_text(), ".11\n111\n111\n\n111\n111\n111\n\n111\n111\n111");
+
+        // A single slice has nothing above or below it.
+        let mut flat = Map::try_from(full).unwrap();
+        flat.maximize(false);
+        assert_eq!(flat.total_sum(), 10);
+
+        assert_eq!(Map::try_from("##\n#a").err(), Some(ParseError::InvalidTile('a')));
+    }
+
+    #[test]
+    fn test_edges() {
+        // Cells on the border are next to the ground outside the map, so they can only ever be
+        // dug 1 deep, no matter which cells count as neighbours.
+        for royal in [false, true] {
+            let mut map = Map::try_from("####\n####\n####\n####").unwrap();
+            map.maximize(royal);
+            assert_eq!(map.to_text(), "1111\n1221\n1221\n1111");
+        }
+
+        // Missing cells are at ground level, too.
+        let ragged = "###\n#####\n#####\n####";
+        let mut map = Map::try_from(ragged).unwrap();
+        map.maximize(false);
+        assert_eq!(map.to_text(), "111..\n12211\n12221\n1111.");
+        let mut map = Map::try_from(ragged).unwrap();
+        map.maximize(true);
+        assert_eq!(map.to_text(), "111..\n12111\n12211\n1111.");
+
+        // Slices may be of different sizes, too.
+        let mut map = Map::try_from("###\n###\n###\n\n###\n###\n###\n\n#").unwrap();
+        map.maximize(false);
+        assert_eq!(map.to_text(), "111\n111\n111\n\n111\n111\n111\n\n1..\n...\n...");
+    }
+
+    #[test]
+    fn test_large() {
+        // In a square of earth, every cell is as deep as its distance to the nearest edge.
+        let size = 1000;
+        let input = vec!["#".repeat(size); size].join("\n");
+        let expected: usize = (1..=size / 2).map(|depth| depth * (4 * size + 4 - 8 * depth)).sum();
+        for royal in [false, true] {
+            let mut map = Map::try_from(input.as_str()).unwrap();
+            map.maximize(royal);
+            assert_eq!(map.total_sum(), expected);
+        }
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [127, 2674, 10571];
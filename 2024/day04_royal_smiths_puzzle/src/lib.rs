@@ -1,15 +1,218 @@
-fn strikes_to_align(components: &[isize]) -> isize {
-    components.iter().sum::<isize>() - components.len() as isize * *components.iter().min().unwrap_or(&0)
+use core::fmt::Display;
+use std::io::BufRead;
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    Overflow,
+    ParseIntError(ParseIntError),
+    ReadError(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "The number of strikes is too large to be represented"),
+            Self::ParseIntError(e) => write!(f, "Unable to parse into a number: {e}"),
+            Self::ReadError(e) => write!(f, "Unable to read input: {e}"),
+        }
+    }
+}
+
+impl From<ParseIntError> for ParseError {
+    fn from(value: ParseIntError) -> Self {
+        Self::ParseIntError(value)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AlignmentError {
+    OutOfReach(isize, isize),
+    Overflow,
+}
+
+impl Display for AlignmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfReach(lowest, highest) => write!(f, "No height is within reach of all nails: Some can't go below {lowest}, but others can't go above {highest}."),
+            Self::Overflow => write!(f, "The cost of the alignment is too large to be represented"),
+        }
+    }
 }
 
-fn strikes_to_align_omni(components: &[isize]) -> isize {
-    let sum: isize = components.iter().sum();
-    let len = components.len() as isize;
-    let mut estimate = (sum + len/2) / len;
+/// A nail: Its height, and the cost of a single strike on it. In the notes, the weight may
+/// follow the height, separated by whitespace. It defaults to 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Nail {
+    pub height: isize,
+    pub weight: usize,
+}
+
+impl Nail {
+    pub fn new(height: isize, weight: usize) -> Self {
+        Self { height, weight }
+    }
+}
+
+impl TryFrom<&str> for Nail {
+    type Error = ParseIntError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once(char::is_whitespace) {
+            Some((height, weight)) => Ok(Self { height: height.parse()?, weight: weight.trim().parse()? }),
+            None => Ok(Self { height: value.parse()?, weight: 1 }),
+        }
+    }
+}
+
+/// The result of aligning the nails: The total cost of all strikes, and the height all nails end
+/// up at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Alignment {
+    pub cost: isize,
+    pub target: isize,
+}
+
+// The total cost of moving each nail by its distance, where every strike costs its weight. All
+// costs are computed through here, so that none of them can silently overflow.
+fn checked_cost(moves: impl IntoIterator<Item = (usize, usize)>) -> Result<isize, AlignmentError> {
+    moves
+        .into_iter()
+        .try_fold(0_i128, |cost, (distance, weight)| (distance as i128).checked_mul(weight as i128)?.checked_add(cost))
+        .and_then(|cost| isize::try_from(cost).ok())
+        .ok_or(AlignmentError::Overflow)
+}
+
+fn cost_to_align(nails: &[Nail], target: isize) -> Result<isize, AlignmentError> {
+    checked_cost(nails.iter().map(|nail| (nail.height.abs_diff(target), nail.weight)))
+}
+
+/// Like `strikes_to_align()`, but every strike on a nail costs its weight.
+pub fn align_weighted(nails: &[Nail]) -> Result<Alignment, AlignmentError> {
+    let target = nails.iter().map(|nail| nail.height).min().unwrap_or(0);
+    Ok(Alignment { cost: cost_to_align(nails, target)?, target })
+}
+
+/// Like `strikes_to_align_omni()`, but every strike on a nail costs its weight. The cheapest
+/// target is a weighted median of the heights, i. e. one where neither the nails below nor the
+/// ones above it weigh more than half the total.
+pub fn align_omni_weighted(nails: &[Nail]) -> Result<Alignment, AlignmentError> {
+    let target = weighted_median(nails);
+    Ok(Alignment { cost: cost_to_align(nails, target)?, target })
+}
+
+fn weighted_median(nails: &[Nail]) -> isize {
+    let mut sorted = nails.to_vec();
+    sorted.sort_by_key(|nail| nail.height);
+    // A `u128` can hold the sum of more weights than fit into memory, even doubled.
+    let total: u128 = nails.iter().map(|nail| nail.weight as u128).sum();
+    let mut below = 0;
+    sorted.iter().find(|nail| {
+        below += nail.weight as u128;
+        2 * below >= total
+    }).map(|nail| nail.height).unwrap_or(0)
+}
+
+/// How far a nail can be moved: At most `down` strikes down and `up` strikes up, where `None`
+/// means there is no limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Reach {
+    pub down: Option<usize>,
+    pub up: Option<usize>,
+}
+
+impl Reach {
+    pub fn new(down: Option<usize>, up: Option<usize>) -> Self {
+        Self { down, up }
+    }
+
+    /// No limits at all, as in part 3.
+    pub fn unlimited() -> Self {
+        Self { down: None, up: None }
+    }
+
+    /// The nail can only be hammered down, as in parts 1 and 2.
+    pub fn down_only() -> Self {
+        Self { down: None, up: Some(0) }
+    }
+
+    /// The nail can be moved at most `max` in either direction.
+    pub fn within(max: usize) -> Self {
+        Self { down: Some(max), up: Some(max) }
+    }
+}
+
+/// The cheapest alignment, where each nail can only be moved within its `Reach`. The cost is
+/// convex in the target height, so the best feasible target is the weighted median, moved into
+/// the range all nails can reach.
+pub fn align_bounded(nails: &[(Nail, Reach)]) -> Result<Alignment, AlignmentError> {
+    let lowest = nails.iter().map(|(nail, reach)| reach.down.map(|down| nail.height.saturating_sub_unsigned(down)).unwrap_or(isize::MIN)).max().unwrap_or(0);
+    let highest = nails.iter().map(|(nail, reach)| reach.up.map(|up| nail.height.saturating_add_unsigned(up)).unwrap_or(isize::MAX)).min().unwrap_or(0);
+    if lowest > highest {
+        return Err(AlignmentError::OutOfReach(lowest, highest));
+    }
+    let nails: Vec<_> = nails.iter().map(|(nail, _reach)| *nail).collect();
+    let target = weighted_median(&nails).clamp(lowest, highest);
+    Ok(Alignment { cost: cost_to_align(&nails, target)?, target })
+}
+
+/// The cheapest ways to align the nails: Their cost, and all heights the nails can be aligned to
+/// at that cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OptimalAlignments {
+    pub cost: isize,
+    pub targets: RangeInclusive<isize>,
+}
+
+/// The cheapest alignments if nails can be hammered up or down. Any height between the lower and
+/// upper median is optimal, so for an odd number of nails there is just one.
+pub fn align_omni(components: &[isize]) -> Result<OptimalAlignments, AlignmentError> {
+    if components.is_empty() {
+        return Ok(OptimalAlignments { cost: 0, targets: 0..=0 });
+    }
+    let mut components = components.to_vec();
+    let len = components.len();
+    let upper = *components.select_nth_unstable(len / 2).1;
+    // After selecting the upper median, everything before it is no larger than it, so the lower
+    // median is the largest of those.
+    let lower = if len.is_multiple_of(2) { components[..len / 2].iter().max().copied().unwrap_or(upper) } else { upper };
+    Ok(OptimalAlignments { cost: strikes_to(&components, lower)?, targets: lower..=upper })
+}
+
+// The number of strikes needed to move all `components` to `target`.
+fn strikes_to(components: &[isize], target: isize) -> Result<isize, AlignmentError> {
+    checked_cost(components.iter().map(|c| (c.abs_diff(target), 1)))
+}
+
+/// The number of strikes needed to hammer all `components` down to the lowest one.
+pub fn strikes_to_align(components: &[isize]) -> Result<isize, AlignmentError> {
+    strikes_to(components, components.iter().min().copied().unwrap_or(0))
+}
+
+/// The number of strikes needed to align all `components` to the same height, if they can be
+/// hammered up or down.
+pub fn strikes_to_align_omni(components: &[isize]) -> Result<isize, AlignmentError> {
+    if components.is_empty() {
+        return Ok(0);
+    }
+    // The mean lies between the lowest and highest component, so it fits into an `isize`, even
+    // if the sum doesn't.
+    let sum: i128 = components.iter().map(|c| *c as i128).sum();
+    let len = components.len() as i128;
+    let mut estimate = ((sum + len/2) / len) as isize;
     
-    let mut y0: isize = components.iter().map(|c| c.abs_diff(estimate) as isize).sum();
-    let mut y1: isize = components.iter().map(|c| c.abs_diff(estimate+1) as isize).sum();
-    let y_m1: isize = components.iter().map(|c| c.abs_diff(estimate-1) as isize).sum();
+    // The cost is convex, so once it stops decreasing, we have found the minimum. Targets beyond
+    // the range of an `isize` are never better than its ends.
+    let mut y0 = strikes_to(components, estimate)?;
+    let mut y1 = strikes_to(components, estimate.saturating_add(1))?;
+    let y_m1 = strikes_to(components, estimate.saturating_sub(1))?;
     let step;
 
     if y1 < y0 {
@@ -18,24 +221,51 @@ fn strikes_to_align_omni(components: &[isize]) -> isize {
         step = -1;
         y1 = y_m1;
     } else {
-        return y0;
+        return Ok(y0);
     }
 
     loop {
         y0 = y1;
         estimate += step;
-        y1 = components.iter().map(|c| c.abs_diff(estimate+step) as isize).sum();
-        if y1 > y0 {
-            return y0;
+        y1 = strikes_to(components, estimate.saturating_add(step))?;
+        if y1 >= y0 {
+            return Ok(y0);
+        }
+    }
+}
+
+/// Like `align_weighted()`, but reading the nails one line at a time from `reader`, so they never
+/// have to be in memory all at once. Since the cost is the weighted sum of the heights minus the
+/// lowest height times the total weight, a single pass is enough to compute it. Returns the cost
+/// or `ParseError::Overflow` if any of these doesn't fit into an `i128`.
+pub fn strikes_to_align_streaming(reader: impl BufRead) -> Result<i128, ParseError> {
+    let mut sum: i128 = 0;
+    let mut total_weight: i128 = 0;
+    let mut lowest = None;
+    for line in reader.lines() {
+        let line = line.map_err(|e| ParseError::ReadError(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
         }
+        let nail = Nail::try_from(line.trim())?;
+        let weight = nail.weight as i128;
+        sum = (nail.height as i128).checked_mul(weight).and_then(|strikes| sum.checked_add(strikes)).ok_or(ParseError::Overflow)?;
+        total_weight = total_weight.checked_add(weight).ok_or(ParseError::Overflow)?;
+        lowest = Some(lowest.unwrap_or(nail.height).min(nail.height));
     }
+    let lowest = lowest.unwrap_or(0) as i128;
+    lowest.checked_mul(total_weight).and_then(|base| sum.checked_sub(base)).ok_or(ParseError::Overflow)
 }
 
-pub fn run(input: &str, part: usize) -> Result<isize, std::num::ParseIntError> {
-    let nails: Vec<_> = input.lines().map(|l| l.parse::<isize>()).collect::<Result<Vec<_>, _>>()?;
+pub fn run(input: &str, part: usize) -> Result<i128, ParseError> {
     match part {
-        1 | 2 => Ok(strikes_to_align(&nails)),
-        3 => Ok(strikes_to_align_omni(&nails)),
+        1 | 2 => strikes_to_align_streaming(input.as_bytes()),
+        3 => {
+            // Like the streaming path, we skip blank lines.
+            let nails: Vec<_> = input.lines().map(str::trim).filter(|line| !line.is_empty()).map(Nail::try_from).collect::<Result<Vec<_>, _>>()?;
+            // Without limits on the reach, the only way to fail is an overflow.
+            align_omni_weighted(&nails).map(|alignment| alignment.cost as i128).map_err(|_| ParseError::Overflow)
+        },
         _ => panic!("Illegal part number"),
     }
 }
@@ -46,7 +276,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -58,6 +288,94 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_weighted() {
+        let nails: Vec<_> = ["2 1", "4", "5 1", "6 1", "8"].into_iter().map(Nail::try_from).collect::<Result<_, _>>().unwrap();
+        assert_eq!(align_omni_weighted(&nails), Ok(Alignment { cost: 8, target: 5 }));
+
+        // Striking the nail at 8 is so expensive, everything else is moved up to it instead.
+        let nails: Vec<_> = ["2", "4", "5", "6", "8 10"].into_iter().map(Nail::try_from).collect::<Result<_, _>>().unwrap();
+        assert_eq!(align_weighted(&nails), Ok(Alignment { cost: 2 + 3 + 4 + 60, target: 2 }));
+        assert_eq!(align_omni_weighted(&nails), Ok(Alignment { cost: 6 + 4 + 3 + 2, target: 8 }));
+        assert_eq!(run("2\n4\n5\n6\n8 10", 3), Ok(15));
+        assert_eq!(run("2\n\n4\n5 \n6\n8 10\n", 3), Ok(15));
+
+        assert!(Nail::try_from("3 -1").is_err());
+        assert_eq!(align_omni_weighted(&[]), Ok(Alignment { cost: 0, target: 0 }));
+
+        // Costs too large for an `isize`, whether from the distance or the weights.
+        let far = [isize::MIN, isize::MAX].map(|height| Nail { height, weight: 1 });
+        assert_eq!(align_omni_weighted(&far), Err(AlignmentError::Overflow));
+        let heavy = [0, 10].map(|height| Nail { height, weight: usize::MAX });
+        assert_eq!(align_omni_weighted(&heavy), Err(AlignmentError::Overflow));
+        assert_eq!(align_weighted(&heavy), Err(AlignmentError::Overflow));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let nails = [2, 4, 5, 6, 8].map(|height| Nail { height, weight: 1 });
+        let bounded = |reach: Reach| nails.iter().map(|nail| (*nail, reach)).collect::<Vec<_>>();
+        assert_eq!(align_bounded(&bounded(Reach::down_only())), Ok(Alignment { cost: 15, target: 2 }));
+        assert_eq!(align_bounded(&bounded(Reach::unlimited())), Ok(Alignment { cost: 8, target: 5 }));
+        assert_eq!(align_bounded(&bounded(Reach::within(3))), Ok(Alignment { cost: 8, target: 5 }));
+        assert_eq!(align_bounded(&bounded(Reach::within(2))), Err(AlignmentError::OutOfReach(6, 4)));
+
+        // The nail at 2 can only go up to 3, so everything else has to come down to it.
+        let mut nails = bounded(Reach::unlimited());
+        nails[0].1 = Reach { down: Some(0), up: Some(1) };
+        assert_eq!(align_bounded(&nails), Ok(Alignment { cost: 1 + 1 + 2 + 3 + 5, target: 3 }));
+        assert_eq!(align_bounded(&[]), Ok(Alignment { cost: 0, target: 0 }));
+    }
+
+    #[test]
+    fn test_streaming() {
+        let sample_input = read_file("tests/sample1");
+        assert_eq!(strikes_to_align_streaming(sample_input.as_bytes()), Ok(10));
+        assert_eq!(strikes_to_align_streaming("2\n\n4 3\n".as_bytes()), Ok(6));
+
+        // Too large for an `isize`, but not for an `i128`.
+        let huge = format!("{}\n{}", isize::MIN, isize::MAX);
+        assert_eq!(strikes_to_align_streaming(huge.as_bytes()), Ok(u64::MAX as i128));
+        assert_eq!(run(&huge, 1), Ok(u64::MAX as i128));
+        assert_eq!(run(&huge, 3), Err(ParseError::Overflow));
+        assert_eq!(run(&format!("0 {}\n10 {}", usize::MAX, usize::MAX), 3), Err(ParseError::Overflow));
+        let huge = format!("{} {}\n{} {}", isize::MIN, usize::MAX, isize::MAX, usize::MAX);
+        assert_eq!(strikes_to_align_streaming(huge.as_bytes()), Err(ParseError::Overflow));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(align_omni(&[2, 4, 5, 6, 8]), Ok(OptimalAlignments { cost: 8, targets: 5..=5 }));
+        assert_eq!(align_omni(&[8, 2, 6, 4]), Ok(OptimalAlignments { cost: 8, targets: 4..=6 }));
+        assert_eq!(align_omni(&[3, 3, -1, 3]), Ok(OptimalAlignments { cost: 4, targets: 3..=3 }));
+        assert_eq!(align_omni(&[]), Ok(OptimalAlignments { cost: 0, targets: 0..=0 }));
+
+        let far = [isize::MIN, isize::MAX];
+        assert_eq!(align_omni(&far), Err(AlignmentError::Overflow));
+        assert_eq!(strikes_to_align(&far), Err(AlignmentError::Overflow));
+        assert_eq!(strikes_to_align_omni(&far), Err(AlignmentError::Overflow));
+        assert_eq!(strikes_to_align_omni(&[isize::MAX, isize::MAX - 2]), Ok(2));
+
+        // The hill climb has to find the same cost.
+        let mut seed = 42_u64;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as isize % 1000 - 200
+        };
+        for len in 1..50 {
+            let components: Vec<_> = (0..len).map(|_| random()).collect();
+            let alignments = align_omni(&components).unwrap();
+            assert_eq!(strikes_to_align_omni(&components), Ok(alignments.cost));
+            for target in [*alignments.targets.start() - 1, *alignments.targets.end() + 1] {
+                assert!(components.iter().map(|c| c.abs_diff(target) as isize).sum::<isize>() > alignments.cost);
+            }
+        }
+        for file in ["tests/sample3", "tests/challenge3"] {
+            let components: Vec<_> = read_file(file).lines().map(|l| l.parse().unwrap()).collect();
+            assert_eq!(align_omni(&components).map(|alignments| alignments.cost), strikes_to_align_omni(&components));
+        }
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [64, 815526, 120375970];
@@ -1,25 +1,205 @@
-use core::fmt::{Display, Write};
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use core::fmt::Display;
+use core::hash::{Hash, Hasher};
+use std::collections::{HashMap, VecDeque};
+
+use budget::{Budget, Exhausted};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
+    GaveUp(Exhausted),
     GridMalformed(usize, usize, usize),
+    NoRepetition(usize),
+    NoSides,
+    Overflow,
     ParseIntError(String),
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::GaveUp(e) => write!(f, "{e}"),
             Self::GridMalformed(first, idx, len) => write!(f, "Grid is not rectangular: First line has {first} items, but line {idx} has {len}."),
+            Self::NoRepetition(e) => write!(f, "No number is ever shouted {e} times"),
+            Self::NoSides => write!(f, "The rules need at least one side to clap along"),
+            Self::Overflow => write!(f, "The shouted number is too large"),
             Self::ParseIntError(e) => write!(f, "Unable to parse into a number: {e}"),
         }
     }
 }
 
-struct Dancers {
+impl From<Exhausted> for ParseError {
+    fn from(value: Exhausted) -> Self {
+        Self::GaveUp(value)
+    }
+}
+
+/// The side of the next column a clapper dances along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// How the number shouted after each round is formed from the fronts of the columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shout {
+    Concatenation,
+    Sum,
+    Fronts,
+}
+
+/// What is shouted after a round: Either a single number or all the column fronts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Shouted {
+    Number(u128),
+    Fronts(Vec<usize>),
+}
+
+impl Display for Shouted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Fronts(fronts) => write!(f, "{}", fronts.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ")),
+        }
+    }
+}
+
+/// The rules of the dance. Whenever a clapper has been around the whole next column, they
+/// continue along the next side in `sides`, starting over after the last one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    sides: Vec<Side>,
+    shout: Shout,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self { sides: vec![Side::Left, Side::Right], shout: Shout::Concatenation }
+    }
+}
+
+impl Rules {
+    /// Returns `ParseError::NoSides` if `sides` is empty.
+    pub fn new(sides: &[Side], shout: Shout) -> Result<Self, ParseError> {
+        if sides.is_empty() {
+            return Err(ParseError::NoSides);
+        }
+        Ok(Self { sides: sides.to_vec(), shout })
+    }
+}
+
+/// The columns of dancers, front first, the number of rounds danced so far and the rules they
+/// dance by.
+#[derive(Clone, Debug)]
+pub struct Dancers {
     dancers: Vec<VecDeque<usize>>,
     columns: usize,
     round: usize,
+    rules: Rules,
+}
+
+// Two states of the dance are the same if the columns are and it's the same column's turn to
+// clap, no matter how many rounds it took to get there.
+impl PartialEq for Dancers {
+    fn eq(&self, other: &Self) -> bool {
+        self.dancers == other.dancers && self.round % self.columns == other.round % other.columns
+    }
+}
+
+impl Eq for Dancers {}
+
+impl Hash for Dancers {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dancers.hash(state);
+        (self.round % self.columns).hash(state);
+    }
+}
+
+/// A cycle in the states of the dance: After `start` rounds, the state repeats every `length`
+/// rounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// The columns after a round of the dance, and what was shouted then.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Round {
+    pub round: usize,
+    pub columns: Vec<VecDeque<usize>>,
+    pub shouted: Shouted,
+}
+
+/// The rounds as comma separated values, one line per round, after a header line. Each column
+/// lists its dancers front first, separated by spaces.
+pub fn to_csv(rounds: &[Round]) -> String {
+    let columns = rounds.first().map(|round| round.columns.len()).unwrap_or(0);
+    let header = ["round".to_string(), "shouted".to_string()].into_iter().chain((1..=columns).map(|c| format!("column {c}")));
+    let lines = rounds.iter().map(|round| [round.round.to_string(), round.shouted.to_string()]
+        .into_iter()
+        .chain(round.columns.iter().map(|column| column.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ")))
+        .collect::<Vec<_>>()
+        .join(","));
+    [header.collect::<Vec<_>>().join(",")].into_iter().chain(lines).collect::<Vec<_>>().join("\n")
+}
+
+/// The rounds as a JSON array, one object per line.
+pub fn to_json(rounds: &[Round]) -> String {
+    let list = |numbers: &mut dyn Iterator<Item = &usize>| format!("[{}]", numbers.map(|n| n.to_string()).collect::<Vec<_>>().join(","));
+    let lines = rounds.iter().map(|round| {
+        let shouted = match &round.shouted {
+            Shouted::Number(n) => n.to_string(),
+            Shouted::Fronts(fronts) => list(&mut fronts.iter()),
+        };
+        let columns = round.columns.iter().map(|column| list(&mut column.iter())).collect::<Vec<_>>().join(",");
+        format!("{{\"round\":{},\"shouted\":{shouted},\"columns\":[{columns}]}}", round.round)
+    });
+    format!("[\n{}\n]", lines.collect::<Vec<_>>().join(",\n"))
+}
+
+/// An endless iterator over the rounds of the dance, yielding the number of each round and what
+/// was shouted after it.
+pub struct Rounds {
+    dancers: Dancers,
+}
+
+impl Rounds {
+    /// The state of the dance after the last round yielded.
+    pub fn dancers(&self) -> &Dancers {
+        &self.dancers
+    }
+
+    /// Also yield the columns after each round, e. g. to export them. This copies all columns
+    /// every round, so it's much slower than just dancing.
+    pub fn with_columns(self) -> Snapshots {
+        Snapshots { rounds: self }
+    }
+}
+
+impl Iterator for Rounds {
+    type Item = Result<(usize, Shouted), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.dancers.dance().map(|shouted| (self.dancers.round, shouted)))
+    }
+}
+
+/// Like `Rounds`, but yielding the full `Round`, including the columns.
+pub struct Snapshots {
+    rounds: Rounds,
+}
+
+impl Iterator for Snapshots {
+    type Item = Result<Round, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.rounds.next()?;
+        Some(next.map(|(round, shouted)| Round { round, columns: self.rounds.dancers.dancers.clone(), shouted }))
+    }
 }
 
 impl TryFrom<&str> for Dancers {
@@ -35,76 +215,212 @@ impl TryFrom<&str> for Dancers {
             }
             numbers.iter().enumerate().for_each(|(x, n)| dancers[x].push_back(*n));
         }
-        Ok(Self { dancers, columns, round: 0 })
+        Ok(Self { dancers, columns, round: 0, rules: Rules::default() })
     }
 }
 
 impl Dancers {
-    fn dance(&mut self) -> usize {
+    pub fn with_rules(self, rules: Rules) -> Self {
+        Self { rules, ..self }
+    }
+
+    pub fn rounds(self) -> Rounds {
+        Rounds { dancers: self }
+    }
+
+    pub fn dancers(&self) -> &[VecDeque<usize>] {
+        &self.dancers
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Dance one round: The front dancer of the current column claps around the next column.
+    /// Returns what is shouted after the round, as formed from the column fronts by the rules,
+    /// or `ParseError::Overflow` if that number doesn't fit into a `u128`.
+    pub fn dance(&mut self) -> Result<Shouted, ParseError> {
+        self.step();
+        self.shout()
+    }
+
+    fn step(&mut self) {
         let clapper = self.dancers[self.round % self.columns].pop_front().unwrap();
         self.round += 1;
         let column = &mut self.dancers[self.round % self.columns];
-        let direction = (clapper-1) / column.len();
+        let sides = &self.rules.sides;
         let residual = clapper % column.len();
-        if direction & 1 == 0 {
-            // left side
-            if residual == 0 {
+        match sides[(clapper-1) / column.len() % sides.len()] {
+            Side::Left => if residual == 0 {
                 column.insert(column.len()-1, clapper);
             } else {
                 column.insert(residual-1, clapper);
-            }
-        } else {
-            //right side
-            if residual == 0 {
+            },
+            Side::Right => if residual == 0 {
                 column.insert(1, clapper)
             } else {
                 column.insert(column.len()-residual+1, clapper)
+            },
+        }
+    }
+
+    fn shout(&self) -> Result<Shouted, ParseError> {
+        let mut fronts = self.dancers.iter().map(|c| *c.front().unwrap());
+        match self.rules.shout {
+            Shout::Concatenation => fronts
+                .try_fold(0_u128, |number, front| {
+                    let front = front as u128;
+                    let mut shift = 10;
+                    while shift <= front {
+                        shift *= 10;
+                    }
+                    number.checked_mul(shift)?.checked_add(front)
+                })
+                .map(Shouted::Number)
+                .ok_or(ParseError::Overflow),
+            Shout::Sum => fronts
+                .try_fold(0_u128, |sum, front| sum.checked_add(front as u128))
+                .map(Shouted::Number)
+                .ok_or(ParseError::Overflow),
+            Shout::Fronts => Ok(Shouted::Fronts(fronts.collect())),
+        }
+    }
+
+    /// Find the cycle the dance eventually enters, using Brent's algorithm. There are only
+    /// finitely many ways to arrange the dancers, and each state determines the next one, so
+    /// the dance must enter a cycle at some point. Every dance counts as one step of the
+    /// `budget`.
+    pub fn find_cycle(&self, budget: &mut Budget) -> Result<Cycle, Exhausted> {
+        let mut dance = |dancers: &mut Self| {
+            budget.tick()?;
+            dancers.step();
+            Ok(())
+        };
+
+        // Find the length by letting the hare run ahead of the tortoise in steps of powers of 2,
+        // until it catches up with it.
+        let mut power = 1;
+        let mut length = 1;
+        let mut tortoise = self.clone();
+        let mut hare = self.clone();
+        dance(&mut hare)?;
+        while tortoise != hare {
+            if power == length {
+                tortoise = hare.clone();
+                power *= 2;
+                length = 0;
             }
+            dance(&mut hare)?;
+            length += 1;
+        }
+
+        // With the hare `length` rounds ahead, both meet where the cycle starts.
+        let mut tortoise = self.clone();
+        let mut hare = self.clone();
+        for _ in 0..length {
+            dance(&mut hare)?;
         }
-        self.dancers.iter().fold(String::new(), |mut output, c| {
-            let _ = write!(output, "{}", c.front().unwrap());
-            output
-        }).parse().unwrap()
+        let mut start = 0;
+        while tortoise != hare {
+            dance(&mut tortoise)?;
+            dance(&mut hare)?;
+            start += 1;
+        }
+        Ok(Cycle { start, length })
     }
 }
 
+/// The first thing to be shouted for the `n`th time and the number of rounds until then, or
+/// `None` if nothing is ever shouted that often. Looks for the cycle of the dance alongside,
+/// using Brent's algorithm, so this terminates either way.
+fn nth_shout(dancers: &Dancers, n: usize, budget: &mut Budget) -> Result<Option<(Shouted, usize)>, ParseError> {
+    let mut counts = HashMap::new();
+    let mut hare = dancers.clone().rounds();
+    let mut shout = |hare: &mut Rounds, counts: &mut HashMap<Shouted, usize>| {
+        budget.tick()?;
+        let (round, shouted) = hare.next().unwrap()?;
+        let count = counts.entry(shouted.clone()).or_insert(0);
+        *count += 1;
+        Ok::<_, ParseError>((shouted, round - dancers.round, *count == n))
+    };
+
+    let mut tortoise = dancers.clone();
+    let mut power = 1;
+    let mut length = 0;
+    loop {
+        let (shouted, round, done) = shout(&mut hare, &mut counts)?;
+        if done {
+            return Ok(Some((shouted, round)));
+        }
+        length += 1;
+        if hare.dancers() == &tortoise {
+            break;
+        }
+        if length == power {
+            tortoise = hare.dancers().clone();
+            power *= 2;
+            length = 0;
+        }
+    }
+
+    // The hare is in the cycle now, so what is shouted in the next `length` rounds is shouted
+    // again in the same order every `length` rounds after that.
+    let mut period: HashMap<Shouted, Vec<usize>> = HashMap::new();
+    for _ in 0..length {
+        let (shouted, round, done) = shout(&mut hare, &mut counts)?;
+        if done {
+            return Ok(Some((shouted, round)));
+        }
+        period.entry(shouted).or_default().push(round);
+    }
+    Ok(period
+        .iter()
+        .map(|(shouted, rounds)| {
+            let missing = n - counts[shouted] - 1;
+            (rounds[missing % rounds.len()] + (missing / rounds.len() + 1) * length, shouted)
+        })
+        .min()
+        .map(|(round, shouted)| (shouted.clone(), round)))
+}
+
 pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
-    let mut dancers = Dancers::try_from(input)?;
+    run_with_budget(input, part, &mut Budget::unlimited())
+}
+
+/// Like `run()`, but gives up with `ParseError::GaveUp` once the `budget` (counted in dances) is
+/// exhausted, instead of dancing on forever if no answer is found.
+pub fn run_with_budget(input: &str, part: usize, budget: &mut Budget) -> Result<usize, ParseError> {
+    let dancers = Dancers::try_from(input)?;
+    let number = |shouted: Shouted| match shouted {
+        Shouted::Number(n) => usize::try_from(n).map_err(|_| ParseError::Overflow),
+        Shouted::Fronts(_) => unreachable!("The default rules only shout numbers"),
+    };
     match part {
         1 => {
-            for _ in 0..9 { dancers.dance(); }
-            Ok(dancers.dance())
+            let mut rounds = dancers.rounds().map(|round| {
+                budget.tick()?;
+                round
+            });
+            let (_round, shouted) = rounds.nth(9).unwrap()?;
+            number(shouted)
         },
         2 => {
-            // There are probably loops in the results to be exploited here, but I don't see how
-            // to spot them algorithmically and saving the entire state after each dance, just
-            // to look for repetitions, seems excessive.
-            let mut numbers = HashMap::new();
-            loop {
-                let this = dancers.dance();
-                let repetitions: usize = *numbers.get(&this).unwrap_or(&0);
-                if repetitions == 2023 {
-                    return Ok(this * dancers.round);
-                } else {
-                    numbers.insert(this, repetitions+1);
-                }
-            }
+            const REPETITIONS: usize = 2024;
+            let (shouted, round) = nth_shout(&dancers, REPETITIONS, budget)?.ok_or(ParseError::NoRepetition(REPETITIONS))?;
+            number(shouted)?.checked_mul(round).ok_or(ParseError::Overflow)
         },
         3 => {
-            // Admittedly, there is no proof that no more higher numbers will appear later. 
-            // But I simply hope that at the point at which a number repeats itself for the 
-            // 10th time, no more new ones will appear.
-            let mut numbers = BTreeMap::new();
-            loop {
-                let this = dancers.dance();
-                let repetitions: usize = *numbers.get(&this).unwrap_or(&0);
-                if repetitions == 9 {
-                    return Ok(numbers.pop_last().unwrap().0);
-                } else {
-                    numbers.insert(this, repetitions+1);
-                }
-            }
-
+            // Every state of the dance, and thus every number shouted, appears before the end of
+            // the first period of the cycle.
+            let cycle = dancers.find_cycle(budget)?;
+            dancers
+                .rounds()
+                .take(cycle.start + cycle.length)
+                .try_fold(0, |max, round| {
+                    budget.tick()?;
+                    let (_round, shouted) = round?;
+                    Ok(max.max(number(shouted)?))
+                })
         },
         _ => panic!("Illegal part number"),
     }
@@ -116,7 +432,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -136,4 +452,77 @@ mod tests {
             assert_eq!(run(&challenge_input, part), Ok(expected[part-1]));
         }
     }
+
+    #[test]
+    fn test_budget() {
+        let sample_input = read_file("tests/sample2");
+        assert_eq!(run_with_budget(&sample_input, 1, &mut Budget::unlimited().with_steps(10)), Ok(6254));
+        let challenge_input = read_file("tests/challenge2");
+        assert_eq!(run_with_budget(&challenge_input, 2, &mut Budget::unlimited().with_steps(1000)), Err(ParseError::GaveUp(Exhausted::Steps(1000))));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let sample_input = read_file("tests/sample2");
+        let mut dancers = Dancers::try_from(sample_input.as_str()).unwrap();
+        assert_eq!(dancers.find_cycle(&mut Budget::unlimited()), Ok(Cycle { start: 3, length: 4 }));
+
+        let start = dancers.clone();
+        (0..4).for_each(|_| { dancers.step(); });
+        assert_ne!(dancers, start);
+        (0..3).for_each(|_| { dancers.step(); });
+        let repeated = dancers.clone();
+        (0..4).for_each(|_| { dancers.step(); });
+        assert_eq!(dancers, repeated);
+
+        // The sample only ever shouts 4 numbers, so the 2024th repetition is extrapolated.
+        assert_eq!(run_with_budget(&sample_input, 2, &mut Budget::unlimited().with_steps(20)), Ok(50877075));
+    }
+
+    #[test]
+    fn test_rules() {
+        let sample_input = read_file("tests/sample1");
+        let dancers = Dancers::try_from(sample_input.as_str()).unwrap();
+        assert_eq!(dancers.clone().dance(), Ok(Shouted::Number(3345)));
+
+        let mut sum = dancers.clone().with_rules(Rules::new(&[Side::Left, Side::Right], Shout::Sum).unwrap());
+        assert_eq!(sum.dance(), Ok(Shouted::Number(15)));
+
+        // Starting on the right side, the first clapper ends up 2nd from the back instead.
+        let mut mirrored = dancers.with_rules(Rules::new(&[Side::Right, Side::Left], Shout::Fronts).unwrap());
+        assert_eq!(mirrored.dance(), Ok(Shouted::Fronts(vec![3, 3, 4, 5])));
+        assert_eq!(mirrored.dancers()[1], [3, 4, 5, 2, 2]);
+
+        assert_eq!(Rules::new(&[], Shout::Sum), Err(ParseError::NoSides));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // 3 times 10 digits exceed a usize, but still fit into a u128.
+        let wide = "1000000000 2000000000 3000000000\n4000000000 5000000000 6000000000";
+        let mut dancers = Dancers::try_from(wide).unwrap();
+        assert_eq!(dancers.dance(), Ok(Shouted::Number(400000000020000000003000000000)));
+        assert_eq!(run(wide, 1), Err(ParseError::Overflow));
+
+        let wider = "1000000000 2000000000 3000000000 4000000000\n5000000000 6000000000 7000000000 8000000000";
+        assert_eq!(Dancers::try_from(wider).unwrap().dance(), Err(ParseError::Overflow));
+    }
+
+    #[test]
+    fn test_export() {
+        let sample_input = read_file("tests/sample1");
+        let rounds = Dancers::try_from(sample_input.as_str()).unwrap().rounds().with_columns().take(2).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(to_csv(&rounds), "round,shouted,column 1,column 2,column 3,column 4
+1,3345,3 4 5,3 2 4 5 2,4 5 2 3,5 2 3 4
+2,3245,3 4 5,2 4 5 2,4 5 3 2 3,5 2 3 4");
+        assert_eq!(to_json(&rounds[..1]), "[
+{\"round\":1,\"shouted\":3345,\"columns\":[[3,4,5],[3,2,4,5,2],[4,5,2,3],[5,2,3,4]]}
+]");
+
+        let mut fronts = Dancers::try_from(sample_input.as_str()).unwrap().with_rules(Rules::new(&[Side::Left, Side::Right], Shout::Fronts).unwrap()).rounds().with_columns();
+        assert_eq!(to_json(&[fronts.next().unwrap().unwrap()]), "[
+{\"round\":1,\"shouted\":[3,3,4,5],\"columns\":[[3,4,5],[3,2,4,5,2],[4,5,2,3],[5,2,3,4]]}
+]");
+        assert_eq!(to_csv(&[]), "round,shouted");
+    }
 }
@@ -2,6 +2,7 @@ use core::fmt::Display;
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     ChildDuplicate(String),
     LineMalformed(String),
@@ -110,11 +111,35 @@ fn find_unique(names: &HashMap<String, usize>,nodes: &[Node], full_name: bool) -
     String::new()
 }
 
+/// The tree of titans, with pests already removed.
+pub struct Tree {
+    names: HashMap<String, usize>,
+    nodes: Vec<Node>,
+}
+
+impl TryFrom<&str> for Tree {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (names, nodes) = try_build_tree(value)?;
+        Ok(Self { names, nodes })
+    }
+}
+
+impl Tree {
+    /// The path from the root to the only fruit at a unique depth, or an empty `String` if
+    /// there is no such fruit. Nodes are represented by their full names if `full_name` is set,
+    /// or by their first letter otherwise.
+    pub fn unique_path(&self, full_name: bool) -> String {
+        find_unique(&self.names, &self.nodes, full_name)
+    }
+}
+
 pub fn run(input: &str, part: usize) -> Result<String, ParseError> {
-    let (names, nodes) = try_build_tree(input)?;
+    let tree = Tree::try_from(input)?;
     match part {
-        1 => Ok(find_unique(&names, &nodes, true)),
-        2 | 3 => Ok(find_unique(&names, &nodes, false)),
+        1 => Ok(tree.unique_path(true)),
+        2 | 3 => Ok(tree.unique_path(false)),
         _ => panic!("Illegal part number"),
     }
 }
@@ -125,7 +150,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -1,6 +1,7 @@
 use core::fmt::Display;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     InputMalformed(String),
     LineMalformed(String),
@@ -17,8 +18,9 @@ impl Display for ParseError {
     }
 }
 
+/// An action on a device plan or a track segment. The start `S` of a track counts as `Remain`.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum Action{ Increase, Decrease, Remain }
+pub enum Action{ Increase, Decrease, Remain }
 
 impl TryFrom<&str> for Action {
     type Error = ParseError;
@@ -33,8 +35,10 @@ impl TryFrom<&str> for Action {
     }
 }
 
+/// A chariot's action plan and the essence gathered with it so far. Plans are ordered by
+/// essence first.
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
-struct Plan {
+pub struct Plan {
     essence: usize,
     power: usize,
     name: String,
@@ -55,7 +59,20 @@ impl TryFrom<&str> for Plan {
 }
 
 impl Plan {
-    fn execute(&mut self, action: Action) {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn essence(&self) -> usize {
+        self.essence
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Execute one `action` and gather essence according to the resulting power.
+    pub fn execute(&mut self, action: Action) {
         match action {
             Action::Increase => self.power += 1,
             Action::Decrease => self.power = self.power.saturating_sub(1),
@@ -65,7 +82,9 @@ impl Plan {
     }
 }
 
-fn parse_track(input: &str) -> String {
+/// Follow the racetrack drawn in `input` clockwise from the start `S` and return its segments as a
+/// `String`, ending with the `S`.
+pub fn parse_track(input: &str) -> String {
     let mut res = String::new();
     let chars: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
     let mut last_pos = (0, 0);
@@ -94,7 +113,9 @@ fn parse_track(input: &str) -> String {
     res
 }
 
-fn race(track: &[Action], plans: &mut [Plan], rounds: usize) {
+/// Let every plan race on `track` for the given number of `rounds`. Segments with `+` or `-`
+/// override the plans' actions.
+pub fn race(track: &[Action], plans: &mut [Plan], rounds: usize) {
     let track_len = track.len();
     plans.iter_mut().for_each(|plan| {
         (0..rounds).for_each(|round|
@@ -141,7 +162,8 @@ fn construct_actions(inc_count: usize, dec_count: usize, rem_count: usize) -> Ve
     res
 }
 
-fn construct_plans(inc_count: usize, dec_count: usize, rem_count: usize) -> Vec<Plan> {
+/// All distinct plans consisting of the given numbers of increase, decrease and remain actions.
+pub fn construct_plans(inc_count: usize, dec_count: usize, rem_count: usize) -> Vec<Plan> {
     let actions = construct_actions(inc_count, dec_count, rem_count);
     actions.iter().map(|a| Plan { essence: 0, power: 10, name: String::new(), actions: a.clone() }).collect()
 }
@@ -188,7 +210,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
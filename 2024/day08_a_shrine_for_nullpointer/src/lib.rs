@@ -23,15 +23,20 @@ use std::num::ParseIntError;
 //     }
 // }
 
-fn pyramid_height(blocks: usize) -> usize {
+/// The height of the smallest pyramid containing at least `blocks` blocks.
+pub fn pyramid_height(blocks: usize) -> usize {
     ((blocks.saturating_sub(1)) as f64).sqrt() as usize + 1
 }
 
-fn pyramid_blocks(height: usize) -> usize {
+/// The number of blocks in a pyramid of the given `height`.
+pub fn pyramid_blocks(height: usize) -> usize {
     height * height
 }
 
-fn construct_hollow_shrine(priests: usize, acolytes: usize, available_blocks: usize) -> usize {
+/// Builds the shrine layer by layer, including the empty space inside the columns, until it needs
+/// at least `available_blocks` blocks. Returns the number of additional blocks required for that
+/// layer.
+pub fn construct_hollow_shrine(priests: usize, acolytes: usize, available_blocks: usize) -> usize {
     let mut thicknesses = vec![1];
     loop {
         let thickness = (thicknesses.last().unwrap() * priests ) % acolytes + acolytes;
@@ -55,7 +60,9 @@ fn construct_hollow_shrine(priests: usize, acolytes: usize, available_blocks: us
     }
 }
 
-fn construct_shrine(priests: usize, acolytes: usize, available_blocks: usize) -> (usize, usize) {
+/// Builds the shrine layer by layer until `available_blocks` are used up. Returns the number of
+/// blocks missing to complete the last layer, and that layer's width.
+pub fn construct_shrine(priests: usize, acolytes: usize, available_blocks: usize) -> (usize, usize) {
     let mut remaining = available_blocks - 1;
     let mut last_thickness = 1;
     let mut layer = 2;
@@ -99,7 +106,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
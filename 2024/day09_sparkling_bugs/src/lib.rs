@@ -1,7 +1,92 @@
+use core::fmt::Display;
 use std::collections::{BTreeSet, HashMap};
 use std::mem;
 use std::num::ParseIntError;
 
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StampsError {
+    MissingOne,
+    Zero,
+}
+
+impl Display for StampsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingOne => write!(f, "Without a stamp of brightness 1, not every brightness can be reached"),
+            Self::Zero => write!(f, "Stamps must have a brightness of at least 1"),
+        }
+    }
+}
+
+/// The available stamps (beetles) and the minimal numbers of them found so far for any
+/// brightness.
+#[derive(Clone, Debug)]
+pub struct Stamps {
+    stamps: Vec<usize>,
+    mem: HashMap<usize, usize>,
+}
+
+impl TryFrom<&[usize]> for Stamps {
+    type Error = StampsError;
+
+    fn try_from(value: &[usize]) -> Result<Self, Self::Error> {
+        if value.contains(&0) {
+            return Err(StampsError::Zero);
+        }
+        if !value.contains(&1) {
+            return Err(StampsError::MissingOne);
+        }
+        let mut stamps = value.to_vec();
+        stamps.sort_by(|a, b| b.cmp(a));
+        stamps.dedup();
+        let mem = stamps.iter().map(|s| (*s, 1)).collect();
+        Ok(Self { stamps, mem })
+    }
+}
+
+impl Stamps {
+    /// The minimal number of stamps needed to reach exactly `brightness`.
+    pub fn required(&mut self, brightness: usize) -> usize {
+        if brightness == 0 {
+            return 0;
+        }
+        required_stamps(brightness, &self.stamps, &mut self.mem)
+    }
+
+    /// The minimal number of stamps needed for two sparkballs, whose brightnesses add up to
+    /// `brightness` and differ by at most 100.
+    pub fn required_split(&mut self, brightness: usize) -> usize {
+        // Since the brightnesses must not differ by more than 100, we know that they must be
+        // `brightness/2+delta` and `brightness` minus that respectively, for some `delta` within [0..=50].
+        // We try all such pairs and return the lowest combined costs we find.
+        // Thanks to memoization, this becomes cheaper for later calls and is free for the second call
+        // at delta=0.
+        (0..=50.min((brightness - brightness/2).saturating_sub(1))).map(|delta|
+            self.required(brightness/2+delta) +
+            self.required(brightness-(brightness/2+delta))
+            ).min().unwrap()
+    }
+
+    /// Remember the costs of all brightnesses that can be reached with up to `rounds` stamps, to
+    /// speed up later lookups.
+    fn precondition(&mut self, rounds: usize) {
+        let mut last = self.stamps.clone();
+        last.reserve(self.stamps.len().pow(rounds as u32 - 1));
+        let mut next = Vec::with_capacity(self.stamps.len().pow(rounds as u32));
+        for round in 2..=rounds+1 {
+            next.clear();
+            last.iter().for_each(|b| self.stamps.iter().for_each(|s| {
+                self.mem.entry(b+s).or_insert_with(|| {
+                    next.push(b+s);
+                    round
+                });
+            }));
+            mem::swap(&mut last, &mut next);
+        }
+    }
+}
+
 // This struct serves only to sort the open set in our A* algorithm.
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct Brightness {
@@ -54,52 +139,23 @@ fn required_stamps(brightness: usize, stamps: &[usize], mem: &mut HashMap<usize,
     0
 }
 
-fn required_stamps_split(brightness: usize, stamps: &[usize], mem: &mut HashMap<usize, usize>) -> usize {
-    // Since the brightnesses must not differ by more than 100, we know that they must be
-    // `brightness/2+delta` and `brightness` minus that respectively, for some `delta` within [0..=50].
-    // We try all such pairs and return the lowest combined costs we find.
-    // Thanks to memoization, this becomes cheaper for later calls and is free for the second call
-    // at delta=0.
-    (0..=50).map(|delta| 
-        required_stamps(brightness/2+delta, stamps, mem) + 
-        required_stamps(brightness-(brightness/2+delta), stamps, mem)
-        ).min().unwrap()
-}
-
 pub fn run(input: &str, part: usize) -> Result<usize, ParseIntError> {
     let brightnesses: Vec<_> = input.lines().map(|n| n.parse()).collect::<Result<Vec<usize>, _>>()?;
     match part {
         1 => {
-            const STAMPS: [usize; 4] = [10, 5, 3, 1];
-            let mut mem = STAMPS.iter().map(|s| (*s, 1)).collect();
-            Ok(brightnesses.iter().map(|b| required_stamps(*b, &STAMPS, &mut mem)).sum())
+            let mut stamps = Stamps::try_from(&[10, 5, 3, 1][..]).unwrap();
+            Ok(brightnesses.iter().map(|b| stamps.required(*b)).sum())
         },
         2 => {
-            const STAMPS: [usize; 10] = [30, 25, 24, 20, 16, 15, 10, 5, 3, 1];
-            let mut mem = STAMPS.iter().map(|s| (*s, 1)).collect();
-            Ok(brightnesses.iter().map(|b| required_stamps(*b, &STAMPS, &mut mem)).sum())
+            let mut stamps = Stamps::try_from(&[30, 25, 24, 20, 16, 15, 10, 5, 3, 1][..]).unwrap();
+            Ok(brightnesses.iter().map(|b| stamps.required(*b)).sum())
         },
         3 => {
-            const STAMPS: [usize; 18] = [101, 100, 75, 74, 50, 49, 38, 37, 30, 25, 24, 20, 16, 15, 10, 5, 3, 1];
-            const ROUNDS: usize = 5;
-            let mut mem: HashMap<usize, usize> = STAMPS.iter().map(|s| (*s, 1)).collect();
-
+            let mut stamps = Stamps::try_from(&[101, 100, 75, 74, 50, 49, 38, 37, 30, 25, 24, 20, 16, 15, 10, 5, 3, 1][..]).unwrap();
             // Precondition the memory to speed up the lookups a bit. This isn't worth the effort
             // for the earlier parts, but here it saves a few seconds.
-            let mut last = STAMPS.to_vec();
-            last.reserve(STAMPS.len().pow(ROUNDS as u32 - 1));
-            let mut next = Vec::with_capacity(STAMPS.len().pow(ROUNDS as u32));
-            for round in 2..=ROUNDS+1 {
-                next.clear();
-                last.iter().for_each(|b| STAMPS.iter().for_each(|s| {
-                    mem.entry(b+s).or_insert_with(|| {
-                        next.push(b+s);
-                        round
-                    });
-                }));
-                mem::swap(&mut last, &mut next);
-            }
-            Ok(brightnesses.iter().map(|b| required_stamps_split(*b, &STAMPS, &mut mem)).sum())
+            stamps.precondition(5);
+            Ok(brightnesses.iter().map(|b| stamps.required_split(*b)).sum())
         },
         _ => panic!("Illegal part number"),
     }
@@ -111,7 +167,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -131,4 +187,15 @@ mod tests {
             assert_eq!(run(&challenge_input, part), Ok(expected[part-1]));
         }
     }
+
+    #[test]
+    fn test_stamps() {
+        assert_eq!(Stamps::try_from(&[3, 5][..]).err(), Some(StampsError::MissingOne));
+        assert_eq!(Stamps::try_from(&[1, 0][..]).err(), Some(StampsError::Zero));
+
+        let mut stamps = Stamps::try_from(&[1, 5, 3, 10, 5][..]).unwrap();
+        assert_eq!((0..=4).map(|b| stamps.required(b)).collect::<Vec<_>>(), [0, 1, 2, 1, 2]);
+        assert_eq!(stamps.required(16), 3);
+        assert_eq!((2..=5).map(|b| stamps.required_split(b)).collect::<Vec<_>>(), [2, 3, 2, 3]);
+    }
 }
@@ -1,6 +1,7 @@
 use core::fmt::Display;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     GridMalformed(String),
     GridOfGridsMalformed,
@@ -17,8 +18,11 @@ impl Display for ParseError {
     }
 }
 
+/// A single 8*8 grid of runes with the runic word in its centre, as far as it is known. The runes
+/// that can be read from the grid alone are filled in when parsing it, the rest is up to
+/// `Wall::solve()`.
 #[derive(Clone)]
-struct Grid {
+pub struct Grid {
     columns: Vec<Vec<char>>,
     rows: Vec<Vec<char>>,
     runic_word: Vec<Vec<char>>,
@@ -36,7 +40,9 @@ impl TryFrom<&str> for Grid {
         let rows = lines[2..6].iter().map(|line| line.chars().take(2).chain(line.chars().skip(6)).collect::<Vec<char>>()).collect();
         let columns = (2..6).map(|col| [0, 1, 6, 7].iter().map(|row| lines[*row].chars().nth(col).unwrap()).collect::<Vec<char>>()).collect();
 
-        Ok(Self { columns, rows, runic_word: vec![vec!['.'; 4]; 4], solved: false, })
+        let mut grid = Self { columns, rows, runic_word: vec![vec!['.'; 4]; 4], solved: false, };
+        grid.fill();
+        Ok(grid)
     }
 }
 
@@ -55,14 +61,16 @@ impl Grid {
         self.solved = false;
     }
     
-    fn runic_word(&mut self) -> String {
-        if !self.solved {
-            self.fill();
-        }
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    /// The runic word in the centre of the grid, read row by row. Unknown symbols are `.`.
+    pub fn runic_word(&self) -> String {
         self.runic_word.iter().flatten().collect()
     }
 
-    fn effective_power(&mut self) -> usize {
+    pub fn effective_power(&self) -> usize {
         self.runic_word().chars().enumerate().map(|(idx, c)| (idx+1)*(c as usize - b'@' as usize)).sum()
     }
 }
@@ -189,14 +197,43 @@ fn solve_grids(grids: &mut [Vec<Grid>]) -> bool {
     any_solved
 }
 
+/// The shrine's wall: Rows of grids, which may share their outer runes with their neighbours.
+pub struct Wall {
+    grids: Vec<Vec<Grid>>,
+}
+
+impl TryFrom<&str> for Wall {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let grids = into_grids(value)?.iter().map(|row| row.iter().map(|g| Grid::try_from(&g[..])).collect::<Result<Vec<_>, _>>()).collect::<Result<Vec<Vec<_>>, _>>()?;
+        Ok(Self { grids })
+    }
+}
+
+impl Wall {
+    pub fn grids(&self) -> impl Iterator<Item = &Grid> {
+        self.grids.iter().flatten()
+    }
+
+    pub fn grids_mut(&mut self) -> impl Iterator<Item = &mut Grid> {
+        self.grids.iter_mut().flatten()
+    }
+
+    /// Fill in unknown runes from the neighbouring grids until no more grids can be solved.
+    pub fn solve(&mut self) {
+        while solve_grids(&mut self.grids) {}
+    }
+}
+
 pub fn run(input: &str, part: usize) -> Result<String, ParseError> {
-    let mut grids = into_grids(input)?.iter().map(|row| row.iter().map(|g| Grid::try_from(&g[..])).collect::<Result<Vec<_>, _>>()).collect::<Result<Vec<Vec<_>>, _>>()?;
+    let mut wall = Wall::try_from(input)?;
     match part {
-        1 => Ok(grids[0][0].runic_word()),
-        2 => Ok(format!("{}", grids.iter_mut().flatten().map(|g| g.effective_power()).sum::<usize>())),
+        1 => Ok(wall.grids[0][0].runic_word()),
+        2 => Ok(format!("{}", wall.grids().map(|g| g.effective_power()).sum::<usize>())),
         3 => {
-            while solve_grids(&mut grids) {}
-            Ok(format!("{}", grids.iter_mut().flatten().filter(|g| g.solved).map(|g| g.effective_power()).sum::<usize>()))
+            wall.solve();
+            Ok(format!("{}", wall.grids().filter(|g| g.solved).map(|g| g.effective_power()).sum::<usize>()))
         },
         _ => panic!("Illegal part number"),
     }
@@ -208,7 +245,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -2,81 +2,123 @@ use core::fmt::Display;
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError<'a> {
     LineMalformed(&'a str),
+    UnknownCategory(&'a str),
 }
 
 impl Display for ParseError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::LineMalformed(e) => write!(f, "Unable to parse malformed line: {e}\nShould be of format:\nA:B,C,D"),
+            Self::UnknownCategory(e) => write!(f, "Termite category {e} has no reproduction cycle"),
         }
     }
 }
 
-type Termite=usize;
-
-fn try_cycles_from(input: &str) -> Result<(Vec<Vec<Termite>>, HashMap<&str, Termite>), ParseError> {
-    let mut names = HashMap::new();
-    let mut res = Vec::new();
-    for line in input.lines() {
-        if let Some((from, to)) = line.split_once(':') {
-            let next_idx = names.len();
-            let from_idx = *names.entry(from).or_insert(next_idx);
-            while res.len() <= from_idx {
-                res.push(Vec::new());
+/// A termite category, as an index into the `PopulationModel`'s categories.
+pub type Termite=usize;
+
+/// The reproduction cycles of all termite categories: After each day, every termite is replaced by
+/// the termites of the categories its cycle lists.
+pub struct PopulationModel<'a> {
+    cycles: Vec<Vec<Termite>>,
+    names: HashMap<&'a str, Termite>,
+}
+
+impl<'a> TryFrom<&'a str> for PopulationModel<'a> {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut names = HashMap::new();
+        let mut cycles = Vec::new();
+        for line in value.lines() {
+            if let Some((from, to)) = line.split_once(':') {
+                let next_idx = names.len();
+                let from_idx = *names.entry(from).or_insert(next_idx);
+                while cycles.len() <= from_idx {
+                    cycles.push(Vec::new());
+                }
+                cycles[from_idx] = to
+                    .split(',')
+                    .map(|name| {
+                        let next_idx = names.len();
+                        *names.entry(name).or_insert(next_idx)
+                    }).collect();
+            } else {
+                return Err(Self::Error::LineMalformed(line));
             }
-            res[from_idx] = to
-                .split(',')
-                .map(|name| {
-                    let next_idx = names.len();
-                    *names.entry(name).or_insert(next_idx)
-                }).collect();
-        } else {
-            return Err(ParseError::LineMalformed(line));
         }
+        // Every category needs a cycle of its own, or we wouldn't know what its termites turn into.
+        if let Some((name, _idx)) = names.iter().find(|(_name, idx)| cycles.get(**idx).is_none_or(|cycle| cycle.is_empty())) {
+            return Err(Self::Error::UnknownCategory(name));
+        }
+        Ok(Self { cycles, names })
     }
-    Ok((res, names))
 }
 
-fn reproduce(cycles: &[Vec<Termite>], population: &mut Vec<usize>, days: usize) {
-    let mut next_gen = Vec::with_capacity(cycles.len());
-    (0..days).for_each(|_| {
-        next_gen = vec![0; cycles.len()];
-        population.iter().enumerate().for_each(|(category, count)| {
-            let children = &cycles[category];
-            children.iter().for_each(|child| {
-                next_gen[*child] += count;
+impl PopulationModel<'_> {
+    /// The number of known termite categories.
+    pub fn categories(&self) -> usize {
+        self.cycles.len()
+    }
+
+    pub fn category(&self, name: &str) -> Option<Termite> {
+        self.names.get(name).copied()
+    }
+
+    pub fn name(&self, category: Termite) -> Option<&str> {
+        self.names.iter().find(|(_name, idx)| **idx == category).map(|(name, _idx)| *name)
+    }
+
+    /// The `population` (the number of termites per category) after reproducing for the given
+    /// number of `days`. Categories missing at the end of `population` have no termites. Returns
+    /// `None` if `population` has more categories than the model knows.
+    pub fn reproduce(&self, population: &[usize], days: usize) -> Option<Vec<usize>> {
+        if population.len() > self.cycles.len() {
+            return None;
+        }
+        let mut population = population.to_vec();
+        population.resize(self.cycles.len(), 0);
+        let mut next_gen = Vec::with_capacity(self.cycles.len());
+        (0..days).for_each(|_| {
+            next_gen = vec![0; self.cycles.len()];
+            population.iter().enumerate().for_each(|(category, count)| {
+                let children = &self.cycles[category];
+                children.iter().for_each(|child| {
+                    next_gen[*child] += count;
+                });
             });
+            std::mem::swap(&mut population, &mut next_gen);
+            next_gen.clear();
         });
-        std::mem::swap(population, &mut next_gen);
-        next_gen.clear();
-    });
+        Some(population)
+    }
+
+    /// The total population after `days`, starting from a single termite of category `start`, or
+    /// `None` if the model doesn't know that category.
+    pub fn population_after(&self, start: Termite, days: usize) -> Option<usize> {
+        let mut termites = vec![0; self.categories()];
+        *termites.get_mut(start)? = 1;
+        self.reproduce(&termites, days).map(|population| population.iter().sum())
+    }
 }
 
-pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
-    let (cycles, names) = try_cycles_from(input)?;
-    let mut termites = vec![0; cycles.len()];
+pub fn run(input: &str, part: usize) -> Result<usize, ParseError<'_>> {
+    let model = PopulationModel::try_from(input)?;
+    let population_of = |name, days| model
+        .category(name)
+        .and_then(|start| model.population_after(start, days))
+        .ok_or(ParseError::UnknownCategory(name));
     match part {
-        1 => {
-            termites[*names.get("A").unwrap()] = 1;
-            reproduce(&cycles, &mut termites, 4);
-            Ok(termites.iter().sum())
-        },
-        2 => {
-            termites[*names.get("Z").unwrap()] = 1;
-            reproduce(&cycles, &mut termites, 10);
-            Ok(termites.iter().sum())
-        },
+        1 => population_of("A", 4),
+        2 => population_of("Z", 10),
         3 => {
             let mut low = usize::MAX;
             let mut high = usize::MIN;
 
-            (0..cycles.len()).for_each(|c| {
-                termites = vec![0; cycles.len()];
-                termites[c] = 1;
-                reproduce(&cycles, &mut termites, 20);
-                let population = termites.iter().sum();
+            (0..model.categories()).filter_map(|c| model.population_after(c, 20)).for_each(|population| {
                 low = low.min(population);
                 high = high.max(population);
             });
@@ -93,7 +135,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -105,6 +147,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unknown_categories() {
+        assert_eq!(PopulationModel::try_from("A:B,C\nB:A").err(), Some(ParseError::UnknownCategory("C")));
+        let model = PopulationModel::try_from("A:B,C\nB:A\nC:A").unwrap();
+        assert_eq!(model.population_after(3, 1), None);
+        assert_eq!(model.reproduce(&[1, 0, 0, 0], 1), None);
+        assert_eq!(model.reproduce(&[1], 1), Some(vec![0, 1, 1]));
+        assert_eq!(run("B:A\nA:B", 1), Ok(1));
+        assert_eq!(run("B:A\nA:B", 2), Err(ParseError::UnknownCategory("Z")));
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [41, 213729, 896125189572];
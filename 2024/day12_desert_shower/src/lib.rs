@@ -1,19 +1,26 @@
 use core::fmt::Display;
 use std::num::ParseIntError;
 
+use budget::{Budget, Exhausted};
+
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError<'a> {
+    GaveUp(Exhausted),
     LineMalformed(&'a str),
     ParseCharError(char),
     ParseIntError(ParseIntError),
+    TargetUnreachable(usize, usize),
 }
 
 impl Display for ParseError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::GaveUp(e) => write!(f, "{e}"),
             Self::LineMalformed(e) => write!(f, "Unable to parse line: {e}. It should be formatted like \"23 42\""),
             Self::ParseCharError(e) => write!(f, "Unable to parse item: {e}"),
             Self::ParseIntError(e) => write!(f, "Error while trying to parse an integer: {e:?}"),
+            Self::TargetUnreachable(x, y) => write!(f, "None of the catapults can hit the target at x={x}, y={y}"),
         }
     }
 }
@@ -24,13 +31,28 @@ impl From<ParseIntError> for ParseError<'_> {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum Phase{ Ascend, Glide, Descend, }
+impl From<Exhausted> for ParseError<'_> {
+    fn from(value: Exhausted) -> Self {
+        Self::GaveUp(value)
+    }
+}
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-struct Coordinates{
-    x: usize, 
-    y: usize,
+/// The phase of a projectile's flight: up diagonally, straight right, then down diagonally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase{ Ascend, Glide, Descend, }
+
+/// Coordinates on the battlefield, where `y` is the height above the ground.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub struct Coordinates{
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Coordinates {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
 }
 
 impl<'a> TryFrom<&'a str> for Coordinates {
@@ -45,15 +67,29 @@ impl<'a> TryFrom<&'a str> for Coordinates {
     }
 }
 
-struct Catapult {
+pub struct Catapult {
     coordinates: Coordinates,
     segment_number: usize,
 }
 
 impl Catapult {
+    pub fn new(coordinates: Coordinates, segment_number: usize) -> Self {
+        Self { coordinates, segment_number }
+    }
+
+    pub fn coordinates(&self) -> Coordinates {
+        self.coordinates
+    }
+
+    /// The segment number (1 for `A`, 2 for `B` and so on), by which the shooting power is
+    /// multiplied to get the ranking value of a hit.
+    pub fn segment_number(&self) -> usize {
+        self.segment_number
+    }
+
     /// If this catapult is able to hit the `target` coordinates, this returns `Some(p)`, where `p`
     /// is the `Phase`, in which it will be hit. If it cannot be hit, `None` is returned.
-    fn can_hit(&self, target: Coordinates) -> Option<Phase> {
+    pub fn can_hit(&self, target: Coordinates) -> Option<Phase> {
         if target.x <= self.coordinates.x {
             // we only ever shoot to the right
             None
@@ -61,7 +97,7 @@ impl Catapult {
             // Special case to avoid underflows and division by zero below.
             // If the target is on equal or lower height than the catapult, we can only ever hit it
             // in descend phase (or not at all), so we don't need to check the other cases
-            if (target.x + target.y - (self.coordinates.x + self.coordinates.y)) % 3 == 0 {
+            if (target.x + target.y - (self.coordinates.x + self.coordinates.y)).is_multiple_of(3) {
                 Some(Phase::Descend)
             } else {
                 None
@@ -80,7 +116,7 @@ impl Catapult {
                         None
                     },
                 2 => Some(Phase::Glide),
-                _ => if (target.x + target.y - (self.coordinates.x + self.coordinates.y)) % 3 == 0 {
+                _ => if (target.x + target.y - (self.coordinates.x + self.coordinates.y)).is_multiple_of(3) {
                         Some(Phase::Descend)
                     } else {
                         None
@@ -93,7 +129,7 @@ impl Catapult {
     /// hit. If the target is being hit in the ascend phase, any power greater than or equal to the
     /// returned value will hit the target. In the other two phases, only this exact value will
     /// hit.
-    fn power_to_hit(&self, target: Coordinates) -> Option<usize> {
+    pub fn power_to_hit(&self, target: Coordinates) -> Option<usize> {
         match self.can_hit(target) {
             Some(Phase::Ascend) | Some(Phase::Glide) => Some(target.y - self.coordinates.y),
             Some(Phase::Descend) => Some((target.x + target.y - (self.coordinates.x + self.coordinates.y)) / 3),
@@ -102,7 +138,9 @@ impl Catapult {
     }
 }
 
-fn try_parse(input: &str) -> Result<(Vec<Catapult>, Vec<Coordinates>), ParseError> {
+/// Parse the battlefield into its catapults and targets. Hard rocks are represented by two targets
+/// at the same spot.
+pub fn try_parse(input: &str) -> Result<(Vec<Catapult>, Vec<Coordinates>), ParseError<'_>> {
     let lines: Vec<_> = input.lines().collect();
     let height = lines.len()-1;
     let mut catapults = Vec::new();
@@ -127,7 +165,13 @@ fn try_parse(input: &str) -> Result<(Vec<Catapult>, Vec<Coordinates>), ParseErro
     Ok((catapults, targets))
 }
 
-pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
+pub fn run(input: &str, part: usize) -> Result<usize, ParseError<'_>> {
+    run_with_budget(input, part, &mut Budget::unlimited())
+}
+
+/// Like `run()`, but gives up with `ParseError::GaveUp` once the `budget` is exhausted. Every
+/// target checked (and in part 3, every point in time tried for each meteor) counts as one step.
+pub fn run_with_budget<'a>(input: &'a str, part: usize, budget: &mut Budget) -> Result<usize, ParseError<'a>> {
     match part {
         1 | 2 => {
             let (catapults, targets) = try_parse(input)?;
@@ -148,15 +192,15 @@ pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
             // .b..BCABCABC
             // .a..ABCABCABC
             // =============
-            let score = targets
+            targets
                 .iter()
                 .map(|&target| {
+                    budget.tick()?;
                     catapults
                         .iter()
                         .find_map(|c| c.power_to_hit(target).map(|p| p * c.segment_number))
-                        .expect("target unreachable")
-                }).sum();
-            Ok(score)
+                        .ok_or(ParseError::TargetUnreachable(target.x, target.y))
+                }).sum()
         },
         3 => {
             let catapults = [
@@ -165,20 +209,23 @@ pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
                 Catapult { coordinates: Coordinates { x: 0, y: 2 }, segment_number: 3 },
             ];
             let meteors = input.lines().map(Coordinates::try_from).collect::<Result<Vec<_>, _>>()?;
-            let score = meteors
+            meteors
                 .iter()
                 .map(|meteor| {
-                    (meteor.x.div_ceil(2)..=(meteor.x).max(meteor.y))
-                        .find_map(|time| {
-                            let target = Coordinates { x: meteor.x - time, y: meteor.y - time };
-                            catapults
-                                .iter()
-                                .filter_map(|c| c.power_to_hit(target).map(|p| p * c.segment_number))
-                                .min()
-                        }).expect("target unreachable")
-                }).sum();
-
-            Ok(score)
+                    // After min(x, y) steps, the meteor has hit the ground or passed our catapults.
+                    for time in meteor.x.div_ceil(2)..=meteor.x.min(meteor.y) {
+                        budget.tick()?;
+                        let target = Coordinates { x: meteor.x - time, y: meteor.y - time };
+                        if let Some(score) = catapults
+                            .iter()
+                            .filter_map(|c| c.power_to_hit(target).map(|p| p * c.segment_number))
+                            .min()
+                        {
+                            return Ok(score);
+                        }
+                    }
+                    Err(ParseError::TargetUnreachable(meteor.x, meteor.y))
+                }).sum()
         },
         _ => panic!("Illegal part number"),
     }
@@ -190,7 +237,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -210,4 +257,11 @@ mod tests {
             assert_eq!(run(&challenge_input, part), Ok(expected[part-1]));
         }
     }
+
+    #[test]
+    fn test_give_up() {
+        assert_eq!(run("1 5", 3), Err(ParseError::TargetUnreachable(1, 5)));
+        let challenge_input = read_file("tests/challenge3");
+        assert_eq!(run_with_budget(&challenge_input, 3, &mut Budget::unlimited().with_steps(10)), Err(ParseError::GaveUp(Exhausted::Steps(10))));
+    }
 }
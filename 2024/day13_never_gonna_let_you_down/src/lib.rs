@@ -2,8 +2,10 @@ use core::fmt::Display;
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     NoEndError,
+    NoPathError,
     NoStartError,
     ParseCharError(char),
 }
@@ -12,51 +14,70 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::NoEndError => write!(f, "Unable to find an end tile"),
+            Self::NoPathError => write!(f, "Unable to find a path from any start tile to the end"),
             Self::NoStartError => write!(f, "Unable to find a start tile"),
             Self::ParseCharError(e) => write!(f, "Unable to parse tile: {e}"),
         }
     }
 }
 
-type Coordinates = (usize, usize);
+pub type Coordinates = (usize, usize);
 
-/// Try to parse the input into:
+/// The parsed maze:
 /// * a `HashMap` from `Coordinates` to their levels,
 /// * a `Vec` containing all starting points as `Coordinates`, and
 /// * the `Coordinates` of the end point.
 ///
 /// The starting and end points are included in the `HashMap` and set to level 0 (as per the
 /// challenge description).
-fn try_parse(input: &str) -> Result<(HashMap<Coordinates, usize>, Vec<Coordinates>, Coordinates), ParseError> {
-    let mut platforms = HashMap::new();
-    let mut start = Vec::new();
-    let mut end = None;
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                'S' => {
-                    start.push((x, y));
-                    platforms.insert((x, y), 0);
-                },
-                'E' => {
-                    end = Some((x, y));
-                    platforms.insert((x, y), 0);
-                },
-                n if n.is_ascii_digit() => _ = platforms.insert((x, y), n as usize - b'0' as usize),
-                '#' | ' ' => (),
-                e => return Err(ParseError::ParseCharError(e)),
+pub struct Maze {
+    platforms: HashMap<Coordinates, usize>,
+    start: Vec<Coordinates>,
+    end: Coordinates,
+}
+
+impl TryFrom<&str> for Maze {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut platforms = HashMap::new();
+        let mut start = Vec::new();
+        let mut end = None;
+
+        for (y, line) in value.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'S' => {
+                        start.push((x, y));
+                        platforms.insert((x, y), 0);
+                    },
+                    'E' => {
+                        end = Some((x, y));
+                        platforms.insert((x, y), 0);
+                    },
+                    n if n.is_ascii_digit() => _ = platforms.insert((x, y), n as usize - b'0' as usize),
+                    '#' | ' ' => (),
+                    e => return Err(Self::Error::ParseCharError(e)),
+                }
             }
         }
-    }
-    if !start.is_empty() {
-        if let Some(end) = end {
-            Ok((platforms, start, end))
+        if !start.is_empty() {
+            if let Some(end) = end {
+                Ok(Self { platforms, start, end })
+            } else {
+                Err(Self::Error::NoEndError)
+            }
         } else {
-            Err(ParseError::NoEndError)
+            Err(Self::Error::NoStartError)
         }
-    } else {
-        Err(ParseError::NoStartError)
+    }
+}
+
+impl Maze {
+    /// The length of the shortest path between the end and any starting point, or `None` if no
+    /// such path exists.
+    pub fn shortest_path(&self) -> Option<usize> {
+        shortest_path(&self.platforms, self.start.clone(), self.end)
     }
 }
 
@@ -67,8 +88,8 @@ struct Position {
 }
 
 /// Use the Dijkstra algorithm to find the shortest path between the end and any starting point
-/// and returns the length of the path. Panics if no such path exists.
-fn shortest_path(platforms: &HashMap<Coordinates, usize>, start: Vec<Coordinates>, end: Coordinates) -> usize {
+/// and returns the length of the path, or `None` if no such path exists.
+fn shortest_path(platforms: &HashMap<Coordinates, usize>, start: Vec<Coordinates>, end: Coordinates) -> Option<usize> {
     let level_delta = |lhs: Coordinates, rhs: Coordinates| -> usize {
         let left = *platforms.get(&lhs).unwrap();
         let right = *platforms.get(&rhs).unwrap();
@@ -79,7 +100,7 @@ fn shortest_path(platforms: &HashMap<Coordinates, usize>, start: Vec<Coordinates
     while let Some(pos) = open_set.pop_first() {
         let curr_coords = pos.coordinates;
         if start.contains(&curr_coords) {
-            return pos.costs_so_far;
+            return Some(pos.costs_so_far);
         }
         if !visited.contains(&curr_coords) {
             visited.insert(curr_coords);
@@ -97,14 +118,14 @@ fn shortest_path(platforms: &HashMap<Coordinates, usize>, start: Vec<Coordinates
                 });
         }
     }
-    panic!("No path found");
+    None
 }
 
 // The part number is irrelevant for this quest, since the Dijkstra function can handle all of them
 // in the same way. However, the parameter is kept for consistency with the other quests.
 pub fn run(input: &str, _part: usize) -> Result<usize, ParseError> {
-    let (platforms, start, end) = try_parse(input)?;
-    Ok(shortest_path(&platforms, start, end))
+    let maze = Maze::try_from(input)?;
+    maze.shortest_path().ok_or(ParseError::NoPathError)
 }
 
 #[cfg(test)]
@@ -113,7 +134,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -125,6 +146,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_no_path() {
+        let maze = Maze::try_from("S0#0E").unwrap();
+        assert_eq!(maze.shortest_path(), None);
+        assert_eq!(run("S0#0E", 1), Err(ParseError::NoPathError));
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [165, 608, 539];
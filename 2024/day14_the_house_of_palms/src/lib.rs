@@ -2,6 +2,7 @@ use core::fmt::Display;
 use std::{collections::{BTreeSet, HashSet}, num::ParseIntError};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     EmptyInput,
     ParseDirError(Option<char>),
@@ -24,8 +25,8 @@ impl From<ParseIntError> for ParseError {
     }
 }
 
-#[derive(PartialEq, Eq)]
-enum Direction { Up, Down, Right, Left, Forward, Backward }
+#[derive(Debug, PartialEq, Eq)]
+pub enum Direction { Up, Down, Right, Left, Forward, Backward }
 
 impl TryFrom<Option<char>> for Direction {
     type Error = ParseError;
@@ -43,7 +44,8 @@ impl TryFrom<Option<char>> for Direction {
     }
 }
 
-struct Schedule {
+/// The growth plan of a single branch: A sequence of directions and distances.
+pub struct Schedule {
     steps: Vec<(Direction, usize)>,
 }
 
@@ -62,7 +64,8 @@ impl TryFrom<&str> for Schedule {
 }
 
 impl Schedule {
-    fn max_height(&self) -> usize {
+    /// The highest point the branch reaches while growing.
+    pub fn max_height(&self) -> usize {
         self.steps
             .iter()
             .fold((0, 0), |(curr, max), next| {
@@ -74,7 +77,8 @@ impl Schedule {
             }).1
     }
 
-    fn segments(&self) -> HashSet<[isize; 3]> {
+    /// All segments the branch grows through, as `[height, right, forward]` coordinates.
+    pub fn segments(&self) -> HashSet<[isize; 3]> {
         let mut curr = [0, 0, 0];
         let mut segments = HashSet::new();
         self.steps.iter().for_each(|(dir, dist)| {
@@ -94,7 +98,8 @@ impl Schedule {
         segments
     }
 
-    fn leaf(&self) -> [isize; 3] {
+    /// The coordinates of the leaf at the branch's end.
+    pub fn leaf(&self) -> [isize; 3] {
         self.steps
             .iter()
             .fold([0, 0, 0], |acc, (dir, dist)| {
@@ -117,7 +122,9 @@ struct Position {
     coordinates: [isize; 3],
 }
 
-fn distance(from: &[isize; 3], to: &[isize; 3], segments: &HashSet<[isize; 3]>) -> Option<usize> {
+/// The length of the shortest path from `from` to `to` through the given `segments`, or `None` if
+/// `to` cannot be reached.
+pub fn distance(from: &[isize; 3], to: &[isize; 3], segments: &HashSet<[isize; 3]>) -> Option<usize> {
     let estimate = |curr: &[isize; 3]| -> usize {
         curr[0].abs_diff(to[0]) + curr[1].abs_diff(to[1]) + curr[2].abs_diff(to[2])
     };
@@ -196,7 +203,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -2,6 +2,7 @@ use core::fmt::Display;
 use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     EmptyInput,
     GridTooBig,
@@ -24,7 +25,7 @@ impl Display for ParseError {
     }
 }
 
-type Coordinates = (usize, usize);
+pub type Coordinates = (usize, usize);
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct Position {
@@ -35,7 +36,9 @@ struct Position {
     collecting: u8,
 }
 
-struct Map {
+/// The herbalist's map: Walkable tiles, the positions of each herb type and the entrance in the
+/// top row.
+pub struct Map {
     walkable: Vec<Vec<bool>>,
     herbs: Vec<Vec<u16>>,
     width: usize,
@@ -91,7 +94,12 @@ impl TryFrom<&str> for Map {
 }
 
 impl Map {
-    fn route(&self, start: Coordinates, dest: Coordinates) -> Option<u16> {
+    pub fn start(&self) -> Coordinates {
+        self.start
+    }
+
+    /// The length of the shortest walk from `start` to `dest`, or `None` if there is none.
+    pub fn route(&self, start: Coordinates, dest: Coordinates) -> Option<u16> {
         let mut open_set = VecDeque::from([(start, 0)]);
         let mut visited = HashSet::from([start]);
         while let Some((pos, dist)) = open_set.pop_front() {
@@ -117,7 +125,9 @@ impl Map {
         None
     }
 
-    fn route_single(&self, herb_idx: usize) -> Option<u16> {
+    /// The length of the shortest walk from the entrance to the nearest herb of type `herb_idx`
+    /// (in order of first appearance).
+    pub fn route_single(&self, herb_idx: usize) -> Option<u16> {
         let mut open_set = VecDeque::from([(self.start, 0)]);
         let mut visited = HashSet::from([self.start]);
         if let Some(targets) = self.herbs.get(herb_idx) {
@@ -146,7 +156,9 @@ impl Map {
         None
     }
 
-    fn route_all_bfs(&self) -> Option<u16> {
+    /// The length of the shortest round trip from the entrance collecting one herb of each type,
+    /// using a breadth-first search over all tiles. Feasible for small maps only.
+    pub fn route_all_bfs(&self) -> Option<u16> {
         let start = ((self.start.0 << 8) + self.start.1) as u16;
         let all_herbs = (1_u16 << self.herbs.len()) - 1;
         let herbs_lut: HashMap<u16, u8> = self.herbs
@@ -186,7 +198,8 @@ impl Map {
         None
     }
 
-    fn route_all_a_star(&self) -> Option<u16> {
+    /// Like `route_all_bfs()`, but using A* over the network of distances between the herbs.
+    pub fn route_all_a_star(&self) -> Option<u16> {
         let start = ((self.start.0 << 8) + self.start.1) as u16;
         let interesting: Vec<(u8, u16)> = self.herbs
             .iter()
@@ -308,7 +321,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
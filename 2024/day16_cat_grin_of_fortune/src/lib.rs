@@ -2,6 +2,7 @@ use core::fmt::Display;
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError<'a> {
     InputMalformed,
     LineMalformed(&'a str),
@@ -18,9 +19,11 @@ impl Display for ParseError<'_> {
     }
 }
 
-type Face = [u8; 3];
+pub type Face = [u8; 3];
 
-struct Configuration {
+/// The slot machine: How far each wheel advances per pull of the right lever, and the faces on
+/// each wheel.
+pub struct Configuration {
     advance_by: Vec<usize>,
     wheels: Vec<Vec<Face>>,
 }
@@ -64,7 +67,7 @@ impl Configuration {
     ///
     /// The absolute value of `adjust` MUST be less than or equal to pull_count, as the calculation
     /// may underflow otherwise. `panic()`s in debug mode otherwise.
-    fn at(&self, pull_count: usize, adjust: isize) -> Vec<[u8; 3]> {
+    pub fn at(&self, pull_count: usize, adjust: isize) -> Vec<[u8; 3]> {
         debug_assert!(adjust.abs_diff(0) <= pull_count);
         self.wheels
             .iter()
@@ -73,7 +76,8 @@ impl Configuration {
             .collect()
     }
     
-    fn print_at(&self, pull_count: usize) -> String {
+    /// The visible faces after `pull_count` pulls, separated by spaces.
+    pub fn print_at(&self, pull_count: usize) -> String {
         self.at(pull_count, 0).iter().map(Self::face).collect::<Vec<_>>().join(" ")
     }
 
@@ -81,7 +85,9 @@ impl Configuration {
         std::str::from_utf8(bytes).unwrap().to_string()
     }
 
-    fn score(faces: &[[u8; 3]]) -> usize {
+    /// The number of coins won for the given faces: Each eye symbol appearing at least three
+    /// times scores one coin for the third occurence and each one after that.
+    pub fn score(faces: &[[u8; 3]]) -> usize {
         // Since we never calculate the score in the way described in part 1 (including muzzles),
         // we only need to worry about the "eyes", i. e. symbols 0 and 2 in our faces.
         let mut symbols = HashMap::new();
@@ -93,7 +99,8 @@ impl Configuration {
         symbols.iter().filter(|(_s, count)| **count > 2).map(|(_s, count)| *count - 2).sum()
     }
 
-    fn score_after(&self, pull_count: usize) -> usize {
+    /// The total number of coins won in `pull_count` pulls of the right lever.
+    pub fn score_after(&self, pull_count: usize) -> usize {
         // All symbols must repeat after a number of pulls equal to the least common multiple of
         // all wheel sizes. If we surpass that number, we can extrapolate any future scores.
         let cycle_len = self.wheels
@@ -117,7 +124,9 @@ impl Configuration {
         }
     }
 
-    fn min_max(&self, pull_count: usize) -> (usize, usize) {
+    /// The minimum and maximum total number of coins that can be won in `pull_count` pulls of the
+    /// right lever, if the left lever may be pulled or pushed once (or left alone) before each.
+    pub fn min_max(&self, pull_count: usize) -> (usize, usize) {
         // After each pull of the right lever, for each sum of pulls - pushes of the left lever, 
         // this Vec will represent the (min, max) coin values that can be won this way at index 
         // [pull_count - pulls + pushes].
@@ -168,7 +177,7 @@ fn lcm(lhs: usize, rhs: usize) -> usize {
     (lhs / gcd(lhs, rhs)).saturating_mul(rhs)
 }
 
-pub fn run(input: &str, part: usize) -> Result<String, ParseError> {
+pub fn run(input: &str, part: usize) -> Result<String, ParseError<'_>> {
     let config = Configuration::try_from(input)?;
     match part {
         1 => Ok(config.print_at(100)),
@@ -187,7 +196,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
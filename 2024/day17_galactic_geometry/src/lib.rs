@@ -1,6 +1,7 @@
 use core::fmt::Display;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     NoStars,
 }
@@ -25,8 +26,9 @@ impl Star {
     }
 }
 
+/// A constellation, connected by the shortest possible (Manhattan distance) lines.
 #[derive(Clone, Debug)]
-struct Constellation {
+pub struct Constellation {
     stars: usize,
     distance: usize,
 }
@@ -89,15 +91,28 @@ impl TryFrom<&str> for Constellation {
 }
 
 impl Constellation {
-    fn size(&self) -> usize {
+    pub fn stars(&self) -> usize {
+        self.stars
+    }
+
+    /// The number of stars plus the total length of the connecting lines.
+    pub fn size(&self) -> usize {
         self.stars + self.distance
     }
 }
 
-struct BrilliantConstellations {
+/// The sky split into constellations, where stars are only connected if they are less than 6
+/// units apart.
+pub struct BrilliantConstellations {
     constellations: Vec<Constellation>
 }
 
+impl BrilliantConstellations {
+    pub fn constellations(&self) -> &[Constellation] {
+        &self.constellations
+    }
+}
+
 impl TryFrom<&str> for BrilliantConstellations {
     type Error = ParseError;
 
@@ -187,7 +202,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -2,6 +2,7 @@ use core::fmt::Display;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     EmptyMap,
     InvalidChar(char),
@@ -16,10 +17,13 @@ impl Display for ParseError {
     }
 }
 
-type Coordinates = (usize, usize);
+/// Coordinates as `(y, x)`, so that they sort row by row.
+pub type Coordinates = (usize, usize);
 
+/// The farm: Walkable tiles, the palm trees' positions and the entries on the border from which
+/// water starts flowing.
 #[derive(Clone)]
-struct Map {
+pub struct Map {
     walkable: Vec<Vec<bool>>,
     trees: Vec<Coordinates>,
     entries: Vec<Coordinates>,
@@ -68,7 +72,9 @@ impl TryFrom<&str> for Map {
 }
 
 impl Map {
-    fn water(&self) -> Vec<usize> {
+    /// The times at which each palm tree is reached by the water, in order. Returns an empty
+    /// `Vec` if not all of them can be reached.
+    pub fn water(&self) -> Vec<usize> {
         let mut open_set = self.entries.iter().map(|e| (*e, 0)).collect::<VecDeque<_>>();
         let mut to_collect = self.trees.clone();
         let mut visited: HashSet<Coordinates> = self.entries.iter().cloned().collect();
@@ -102,7 +108,9 @@ impl Map {
         Vec::new()
     }
 
-    fn best_watering(&mut self) -> Option<usize> {
+    /// Find the spot from which all trees are watered fastest in total, dig a well there and
+    /// return the sum of all watering times. The well is added to the map's entries.
+    pub fn best_watering(&mut self) -> Option<usize> {
         // Find the best spot by watering from the trees and noting how much combined time was
         // spent to reach it. Note that this isn't necessarily the spot we reach first.
         let mut open_set = self.trees.iter().enumerate().map(|(idx, t)| (*t, idx, 0)).collect::<VecDeque<_>>();
@@ -159,7 +167,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
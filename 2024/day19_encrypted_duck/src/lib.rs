@@ -1,6 +1,7 @@
 use core::fmt::Display;
 
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError<'a> {
     InputMalformed(&'a str),
     InvalidOperation(char),
@@ -23,10 +24,11 @@ impl Display for ParseError<'_> {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-enum Operation{ Left, Right, }
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operation{ Left, Right, }
 
-struct Key {
+/// The key: The rotations to apply around each rotation point, repeated as needed.
+pub struct Key {
     operations: Vec<Operation>
 }
 
@@ -43,7 +45,9 @@ impl<'a> TryFrom<&'a str> for Key {
     }
 }
 
-struct Message {
+/// The encrypted message grid. Its `Display`ed form is the text between `>` and `<`.
+#[derive(Clone)]
+pub struct Message {
     chars: Vec<Vec<char>>,
     width: usize,
     height: usize,
@@ -93,7 +97,15 @@ impl Display for Message {
 }
 
 impl Message {
-    fn permutation_cycles(&self, key: &Key) -> Vec<Vec<(usize, usize)>> {
+    /// The full grid, one `String` per row.
+    pub fn rows(&self) -> impl Iterator<Item = String> + '_ {
+        self.chars.iter().map(|row| row.iter().collect())
+    }
+
+    /// Apply one round of decryption using `key` to the grid positions and return the resulting
+    /// permutation as cycles of `(y, x)` positions. A character at position `cycle[i]` moves to
+    /// `cycle[i+1]` in each round.
+    pub fn permutation_cycles(&self, key: &Key) -> Vec<Vec<(usize, usize)>> {
         let mut grid = (0..self.height).map(|y|
                 (0..self.width).map(|x| (y, x)).collect::<Vec<_>>()
             ).collect::<Vec<_>>();
@@ -133,7 +145,9 @@ impl Message {
         res
     }
 
-    fn apply_permutation_cycles(&mut self, cycles: &[Vec<(usize, usize)>], count: usize) {
+    /// Move each character `count` steps along its permutation cycle, which is equivalent to
+    /// `count` rounds of decryption.
+    pub fn apply_permutation_cycles(&mut self, cycles: &[Vec<(usize, usize)>], count: usize) {
         let old = self.chars.clone();
         (0..self.height).for_each(|y|
             (0..self.width).for_each(|x| {
@@ -171,7 +185,7 @@ fn rotate(grid: &mut [Vec<(usize, usize)>], x: usize, y: usize, direction: Opera
     }
 }
 
-pub fn run(input: &str, part: usize) -> Result<String, ParseError> {
+pub fn run(input: &str, part: usize) -> Result<String, ParseError<'_>> {
     if let Some((key, message)) = input.split_once("\n\n") {
         let key = Key::try_from(key)?;
         let mut message = Message::try_from(message)?;
@@ -194,7 +208,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
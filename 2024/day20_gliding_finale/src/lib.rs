@@ -1,9 +1,13 @@
 use core::fmt::Display;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
+use budget::{Budget, Exhausted};
+
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     EmptyMap,
+    GaveUp(Exhausted),
     InvalidChar(char),
     MultipleStartingPositions,
     NoStartingPosition,
@@ -13,6 +17,7 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::EmptyMap => write!(f, "Input doesn't contain a map"),
+            Self::GaveUp(e) => write!(f, "{e}"),
             Self::InvalidChar(e) => write!(f, "Unable to parse {e} into a map tile"), 
             Self::MultipleStartingPositions => write!(f, "Multiple starting positions found"),
             Self::NoStartingPosition => write!(f, "No starting position found"),
@@ -20,13 +25,20 @@ impl Display for ParseError {
     }
 }
 
+impl From<Exhausted> for ParseError {
+    fn from(value: Exhausted) -> Self {
+        Self::GaveUp(value)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tile { Warm, Cold, Stagnant, Rock }
+pub enum Tile { Warm, Cold, Stagnant, Rock }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Direction { Up, Down, Left, Right, None }
+pub enum Direction { Up, Down, Left, Right, None }
 
-struct Map {
+/// The gliding map: Its tiles, the starting position and the checkpoints in order.
+pub struct Map {
     tiles: Vec<Vec<Tile>>,
     starting: (usize, usize),
     checkpoints: Vec<(usize, usize)>,
@@ -76,8 +88,9 @@ impl TryFrom<&str> for Map {
     }
 }
 
+/// A glider at some altitude and position, facing the direction it last moved in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Glider {
+pub struct Glider {
     altitude: usize,
     coordinates: (usize, usize),
     facing: Direction,
@@ -129,8 +142,30 @@ impl Ord for AltState {
     }
 }
 
+impl Map {
+    pub fn starting(&self) -> (usize, usize) {
+        self.starting
+    }
+
+    pub fn tiles(&self) -> &[Vec<Tile>] {
+        &self.tiles
+    }
+}
+
 impl Glider {
-    fn new(starting_position: (usize, usize), altitude: usize) -> Self {
+    pub fn altitude(&self) -> usize {
+        self.altitude
+    }
+
+    pub fn coordinates(&self) -> (usize, usize) {
+        self.coordinates
+    }
+
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+
+    pub fn new(starting_position: (usize, usize), altitude: usize) -> Self {
         Self { 
             altitude,
             coordinates: starting_position, 
@@ -156,34 +191,48 @@ impl Glider {
         res
     }
 
-    fn fly_max(&self, map: &Map, time: usize) -> usize {
+    /// All gliders this one can become within one second: It moves to any adjacent tile except
+    /// rocks and the one it came from, and must still be airborne afterwards.
+    pub fn moves(&self, map: &Map) -> Vec<Glider> {
+        self.next_tiles(map.tiles.len(), map.tiles[self.coordinates.1].len())
+            .into_iter()
+            .filter_map(|(x, y, facing)| {
+                let altitude = match map.tiles[y][x] {
+                    Tile::Warm => self.altitude + 1,
+                    Tile::Cold => self.altitude.saturating_sub(2),
+                    Tile::Rock => return None,
+                    _ => self.altitude - 1,
+                };
+                (altitude > 0).then_some(Glider { altitude, coordinates: (x, y), facing })
+            }).collect()
+    }
+
+    /// The maximum altitude the glider can have after flying for `time` seconds. Every state
+    /// explored counts as one step of the `budget`.
+    pub fn fly_max(&self, map: &Map, time: usize, budget: &mut Budget) -> Result<usize, Exhausted> {
         let start = AltState { glider: *self, time_remaining: time, };
         let mut open_set = BTreeSet::from([start]);
         let mut visited = HashSet::new();
         while let Some(state) = open_set.pop_first() {
+            budget.tick()?;
             let glider = state.glider;
             if state.time_remaining == 0 {
-                return glider.altitude;
+                return Ok(glider.altitude);
             }
-            glider.next_tiles(map.tiles.len(), map.tiles[glider.coordinates.1].len()).iter().for_each(|&(x, y, facing)| {
-                let tile = map.tiles[y][x];
-                let altitude = match tile {
-                    Tile::Warm => glider.altitude + 1,
-                    Tile::Cold => glider.altitude.saturating_sub(2),
-                    _ => glider.altitude - 1,
-                };
-                let glider = Glider { altitude, coordinates: (x, y), facing };
+            glider.moves(map).into_iter().for_each(|glider| {
                 let next = AltState { glider, time_remaining: state.time_remaining - 1, };
-                if tile != Tile::Rock && altitude > 0 && !visited.contains(&next) {
+                if !visited.contains(&next) {
                     visited.insert(next);
                     open_set.insert(next);
                 }
             });
         }
-        0
+        Ok(0)
     }
 
-    fn race(&self, map: &Map) -> usize {
+    /// The minimal time needed to pass all checkpoints in order and return to the start with at
+    /// least the initial altitude. Every state explored counts as one step of the `budget`.
+    pub fn race(&self, map: &Map, budget: &mut Budget) -> Result<usize, Exhausted> {
         let target_altitude = self.altitude;
         let checkpoints = map.checkpoints.len();
         let estimate = |from: &RaceState| -> usize {
@@ -203,9 +252,10 @@ impl Glider {
         let mut open_set = VecDeque::from([start]);
         let mut visited = HashSet::new();
         while let Some(state) = open_set.pop_front() {
+            budget.tick()?;
             let glider = state.glider;
             if state.checkpoints_remaining == 0 && glider.coordinates == map.starting && glider.altitude >= target_altitude {
-                return state.time_spent;
+                return Ok(state.time_spent);
             }
             let checkpoints_remaining = if state.checkpoints_remaining > 0 && glider.coordinates == map.checkpoints[map.checkpoints.len() - state.checkpoints_remaining] {
                 state.checkpoints_remaining - 1
@@ -229,17 +279,20 @@ impl Glider {
                 }
             });
         }
-        0
+        Ok(0)
     }
 
-    fn glide_max(&self, map: &Map) -> usize {
+    /// The maximum distance southwards the glider can travel before reaching the ground. The map
+    /// repeats infinitely to the south. Every state explored counts as one step of the `budget`.
+    pub fn glide_max(&self, map: &Map, budget: &mut Budget) -> Result<usize, Exhausted> {
         let start = *self;
         let mut open_set = BTreeSet::from([start]);
         let mut visited = HashMap::new();
         while let Some(glider) = open_set.pop_last() {
+            budget.tick()?;
             let (x, y) = glider.coordinates;
             if glider.altitude == 0 {
-                return y;
+                return Ok(y);
             }
             // glider.next_tiles(map.tiles.len(), map.tiles[glider.coordinates.1].len()).iter().for_each(|&(x, y, facing)| {
             [(1, 2, Direction::Down), (0, 1, Direction::Left), (2, 1, Direction::Right)]
@@ -270,24 +323,29 @@ impl Glider {
                 }
             });
         }
-        0
+        Ok(0)
     }
 }
 
 pub fn run(input: &str, part: usize) -> Result<usize, ParseError> {
+    run_with_budget(input, part, &mut Budget::unlimited())
+}
+
+/// Like `run()`, but gives up with `ParseError::GaveUp` once the `budget` is exhausted.
+pub fn run_with_budget(input: &str, part: usize, budget: &mut Budget) -> Result<usize, ParseError> {
     let map = Map::try_from(input)?;
     match part {
         1 => {
             let glider = Glider::new(map.starting, 1000);
-            Ok(glider.fly_max(&map, 100))
+            Ok(glider.fly_max(&map, 100, budget)?)
         },
         2 => {
             let glider = Glider::new(map.starting, 10000);
-            Ok(glider.race(&map))
+            Ok(glider.race(&map, budget)?)
         },
         3 => {
             let glider = Glider::new(map.starting, 384400);
-            Ok(glider.glide_max(&map))
+            Ok(glider.glide_max(&map, budget)?)
         },
         _ => panic!("Illegal part number"),
     }
@@ -299,7 +357,7 @@ mod tests {
     use std::fs::read_to_string;
 
     fn read_file(name: &str) -> String {
-        read_to_string(name).expect(&format!("Unable to read file: {name}")[..]).trim().to_string()
+        read_to_string(name).unwrap_or_else(|_| panic!("Unable to read file: {name}")).trim().to_string()
     }
 
     #[test]
@@ -311,6 +369,12 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sample_budget() {
+        let sample_input = read_file("tests/sample3");
+        assert_eq!(run_with_budget(&sample_input, 3, &mut Budget::unlimited().with_steps(100)), Err(ParseError::GaveUp(Exhausted::Steps(100))));
+    }
+
     #[test]
     fn test_challenge() {
         let expected = [1029, 556, 768792];
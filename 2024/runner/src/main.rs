@@ -0,0 +1,242 @@
+use std::fs::read_to_string;
+use std::process::ExitCode;
+use std::time::Instant;
+
+mod alloc;
+mod quests;
+mod simulations;
+mod store;
+mod tui;
+
+use quests::{Quest, QUESTS};
+use store::{Metadata, Store};
+
+const EVENT: &str = "2024";
+
+#[global_allocator]
+static ALLOCATOR: alloc::CountingAllocator = alloc::CountingAllocator;
+
+const USAGE: &str = "Usage: runner [--sample] [--no-store] [--profile-alloc | --step] [QUEST [PART]]
+       runner --add FILE [--source NAME] [--key KEY] QUEST PART
+
+Runs all parts of the given quest (or of all quests) and prints the results. Challenge notes are
+taken from the note store ($EC_NOTES_DIR, or .notes next to the quests) if present there, or from
+the quests' tests directories otherwise.
+
+Options:
+    --sample          Use the sample notes instead of the challenge notes.
+    --no-store        Ignore the note store and always use the tests directories.
+    --add FILE        Add FILE to the note store as the challenge notes for QUEST and PART.
+    --source NAME     Record NAME (e. g. the account) as the source of the added notes.
+    --key KEY         Record the decryption KEY of the added notes.
+    --profile-alloc   Report the number of allocations, the bytes allocated and the peak heap
+                      usage for each part.
+    --step            Step through the simulation of the given quest interactively. Available
+                      for quests 5, 11, 16, 19 and 20.";
+
+struct Options {
+    kind: &'static str,
+    use_store: bool,
+    profile_alloc: bool,
+    step: bool,
+    add: Option<String>,
+    source: String,
+    key: Option<String>,
+    quest: Option<usize>,
+    part: Option<usize>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        kind: "challenge",
+        use_store: true,
+        profile_alloc: false,
+        step: false,
+        add: None,
+        source: "manual".to_string(),
+        key: None,
+        quest: None,
+        part: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--sample" => options.kind = "sample",
+            "--no-store" => options.use_store = false,
+            "--profile-alloc" => options.profile_alloc = true,
+            "--step" => options.step = true,
+            "--add" => options.add = Some(value()?),
+            "--source" => options.source = value()?,
+            "--key" => options.key = Some(value()?),
+            "-h" | "--help" => return Err(String::new()),
+            number => {
+                let number = number.parse().map_err(|_| format!("Unable to parse argument: {number}"))?;
+                if options.quest.is_none() {
+                    options.quest = Some(number);
+                } else if options.part.is_none() {
+                    options.part = Some(number);
+                } else {
+                    return Err(format!("Unexpected argument: {number}"));
+                }
+            },
+        }
+    }
+    if options.step && options.quest.is_none() {
+        return Err("--step requires a quest number".to_string());
+    }
+    if options.add.is_some() && options.part.is_none() {
+        return Err("--add requires a quest and part number".to_string());
+    }
+    Ok(options)
+}
+
+/// Read the notes for `part` of `quest`: From the store if enabled and the notes are found there,
+/// from the quest's tests directory otherwise.
+fn read_input(quest: &Quest, part: usize, options: &Options) -> Result<String, String> {
+    if options.use_store && options.kind == "challenge" {
+        let note = Store::open_default().latest(EVENT, quest.number, part).map_err(|e| format!("Unable to read note store: {e}"))?;
+        if let Some(note) = note {
+            return Ok(note.content);
+        }
+    }
+    let path = quest.fixture(options.kind, part);
+    read_to_string(&path).map_err(|e| format!("Unable to read {}: {e}", path.display()))
+}
+
+fn has_input(quest: &Quest, part: usize, options: &Options) -> bool {
+    quest.fixture(options.kind, part).exists() ||
+        (options.use_store && options.kind == "challenge" && Store::open_default().latest(EVENT, quest.number, part).is_ok_and(|note| note.is_some()))
+}
+
+fn add(file: &str, quest: &Quest, part: usize, options: &Options) -> Result<(), String> {
+    let content = read_to_string(file).map_err(|e| format!("Unable to read {file}: {e}"))?;
+    let metadata = Metadata::now(&options.source, options.key.as_deref());
+    let hash = Store::open_default()
+        .add(EVENT, quest.number, part, content.trim(), &metadata)
+        .map_err(|e| format!("Unable to add {file} to the note store: {e}"))?;
+    println!("Added {file} as quest {:02} part {part}: {hash}", quest.number);
+    Ok(())
+}
+
+fn step(quest: &Quest, options: &Options) -> Result<(), String> {
+    let part = options.part.unwrap_or(1);
+    let input = read_input(quest, part, options)?;
+    simulations::view(quest.number, input.trim(), part)
+}
+
+fn run_part(quest: &Quest, part: usize, options: &Options) -> Result<(), String> {
+    let input = read_input(quest, part, options)?;
+    let input = input.trim();
+
+    if options.profile_alloc {
+        alloc::start();
+    }
+    let start = Instant::now();
+    let result = (quest.solve)(input, part);
+    let elapsed = start.elapsed();
+    let stats = options.profile_alloc.then(alloc::stop);
+
+    let result = result.map_err(|e| format!("Quest {:02} part {part}: {e}", quest.number))?;
+    print!("Quest {:02} part {part}: {result} ({elapsed:.2?})", quest.number);
+    if let Some(stats) = stats {
+        print!(" [{} allocations, {} bytes, peak {} bytes]", stats.allocations, stats.bytes, stats.peak);
+    }
+    println!();
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{e}\n");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let quests: Vec<&Quest> = match options.quest {
+        Some(number) => match Quest::get(number) {
+            Some(quest) => vec![quest],
+            None => {
+                eprintln!("No such quest: {number}");
+                return ExitCode::FAILURE;
+            },
+        },
+        None => QUESTS.iter().collect(),
+    };
+
+    if let Some(file) = &options.add {
+        return match add(file, quests[0], options.part.unwrap_or(1), &options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            },
+        };
+    }
+
+    if options.step {
+        return match step(quests[0], &options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            },
+        };
+    }
+
+    let mut failed = false;
+    for quest in quests {
+        let parts = options.part.map(|part| part..=part).unwrap_or(1..=3);
+        for part in parts {
+            if options.part.is_none() && !has_input(quest, part, &options) {
+                // Not every quest has notes for all three parts (e. g. some samples are shared).
+                continue;
+            }
+            if let Err(e) = run_part(quest, part, &options) {
+                eprintln!("{e}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = ["--profile-alloc", "5", "2"].iter().map(|s| s.to_string());
+        let options = parse_args(args).unwrap();
+        assert!(options.profile_alloc);
+        assert_eq!(options.kind, "challenge");
+        assert_eq!((options.quest, options.part), (Some(5), Some(2)));
+
+        let args = ["5", "2", "1"].iter().map(|s| s.to_string());
+        assert!(parse_args(args).is_err());
+
+        let args = ["--step"].iter().map(|s| s.to_string());
+        assert!(parse_args(args).is_err());
+
+        let args = ["--add", "notes.txt", "--key", "abc", "3", "1"].iter().map(|s| s.to_string());
+        let options = parse_args(args).unwrap();
+        assert_eq!(options.add.as_deref(), Some("notes.txt"));
+        assert_eq!((options.source.as_str(), options.key.as_deref()), ("manual", Some("abc")));
+
+        let args = ["3", "1", "--source"].iter().map(|s| s.to_string());
+        assert!(parse_args(args).is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let quest = Quest::get(1).unwrap();
+        let input = read_to_string(quest.fixture("sample", 3)).unwrap();
+        assert_eq!((quest.solve)(input.trim(), 3), Ok("30".to_string()));
+    }
+}
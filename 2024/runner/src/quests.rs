@@ -0,0 +1,56 @@
+type Solver = fn(&str, usize) -> Result<String, String>;
+
+pub struct Quest {
+    pub number: usize,
+    pub directory: &'static str,
+    pub solve: Solver,
+}
+
+macro_rules! quest {
+    ($number:expr, $directory:literal, $solver:ident) => {
+        Quest {
+            number: $number,
+            directory: $directory,
+            solve: |input, part| $solver::run(input, part).map(|res| res.to_string()).map_err(|e| e.to_string()),
+        }
+    };
+}
+
+pub const QUESTS: [Quest; 20] = [
+    quest!(1, "day01_The_Battle_for_the_Farmlands", day01_the_battle_for_the_farmlands),
+    quest!(2, "day02_the_kingdom_of_algorithmia", day02_the_kingdom_of_algorithmia),
+    quest!(3, "day03_mining_maestro", day03_mining_maestro),
+    quest!(4, "day04_royal_smiths_puzzle", day04_royal_smiths_puzzle),
+    quest!(5, "day05_pseudo-random_clap_dance", day05_pseudo_random_clap_dance),
+    quest!(6, "day06_the_tree_of_titans", day06_the_tree_of_titans),
+    quest!(7, "day07_not_fast_but_furious", day07_not_fast_but_furious),
+    quest!(8, "day08_a_shrine_for_nullpointer", day08_a_shrine_for_nullpointer),
+    quest!(9, "day09_sparkling_bugs", day09_sparkling_bugs),
+    quest!(10, "day10_shrine_needs_to_shine", day10_shrine_needs_to_shine),
+    quest!(11, "day11_biological_warfare", day11_biological_warfare),
+    quest!(12, "day12_desert_shower", day12_desert_shower),
+    quest!(13, "day13_never_gonna_let_you_down", day13_never_gonna_let_you_down),
+    quest!(14, "day14_the_house_of_palms", day14_the_house_of_palms),
+    quest!(15, "day15_from_the_herbalists_diary", day15_from_the_herbalists_diary),
+    quest!(16, "day16_cat_grin_of_fortune", day16_cat_grin_of_fortune),
+    quest!(17, "day17_galactic_geometry", day17_galactic_geometry),
+    quest!(18, "day18_the_ring", day18_the_ring),
+    quest!(19, "day19_encrypted_duck", day19_encrypted_duck),
+    quest!(20, "day20_gliding_finale", day20_gliding_finale),
+];
+
+impl Quest {
+    pub fn get(number: usize) -> Option<&'static Self> {
+        QUESTS.iter().find(|quest| quest.number == number)
+    }
+
+    /// Path of the fixture for `part`, relative to the runner's manifest. `kind` is either
+    /// `"challenge"` or `"sample"`, as in the quests' own tests.
+    pub fn fixture(&self, kind: &str, part: usize) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(self.directory)
+            .join("tests")
+            .join(format!("{kind}{part}"))
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{stdin, stdout};
+
+use day05_pseudo_random_clap_dance::Dancers;
+use day11_biological_warfare::PopulationModel;
+use day16_cat_grin_of_fortune::Configuration;
+use day19_encrypted_duck::{Key, Message};
+use day20_gliding_finale::{Direction, Glider, Map, Tile};
+
+use crate::tui::{Simulation, Viewer};
+
+/// Quest 5: Each step is one round of the clap dance.
+#[derive(Clone)]
+struct ClapDance {
+    dancers: Dancers,
+    shouted: Option<String>,
+}
+
+impl Simulation for ClapDance {
+    fn step(&mut self) {
+        self.shouted = Some(match self.dancers.dance() {
+            Ok(shouted) => shouted.to_string(),
+            Err(e) => e.to_string(),
+        });
+    }
+
+    fn render(&self) -> String {
+        let columns = self.dancers.dancers();
+        let height = columns.iter().map(VecDeque::len).max().unwrap_or(0);
+        let mut res = (0..height)
+            .map(|y| columns
+                .iter()
+                .map(|column| column.get(y).map(|n| format!("{n:>5}")).unwrap_or(" ".repeat(5)))
+                .collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(shouted) = &self.shouted {
+            res += &format!("\n\nShouted: {shouted}");
+        }
+        res
+    }
+}
+
+/// Quest 11: Each step is one day of termite reproduction.
+#[derive(Clone)]
+struct Termites<'a> {
+    model: &'a PopulationModel<'a>,
+    population: Vec<usize>,
+}
+
+impl Simulation for Termites<'_> {
+    fn step(&mut self) {
+        // The population is built from the model's own categories, so the model knows all of them.
+        if let Some(population) = self.model.reproduce(&self.population, 1) {
+            self.population = population;
+        }
+    }
+
+    fn render(&self) -> String {
+        let categories = self.population
+            .iter()
+            .enumerate()
+            .filter(|(_category, count)| **count > 0)
+            .map(|(category, count)| format!("{:>4}: {count}", self.model.name(category).unwrap_or("?")))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("Total: {}\n\n{categories}", self.population.iter().sum::<usize>())
+    }
+}
+
+/// Quest 16: Each step is one pull of the right lever.
+#[derive(Clone)]
+struct SlotMachine<'a> {
+    configuration: &'a Configuration,
+    pulls: usize,
+    coins: usize,
+}
+
+impl Simulation for SlotMachine<'_> {
+    fn step(&mut self) {
+        self.pulls += 1;
+        self.coins += Configuration::score(&self.configuration.at(self.pulls, 0));
+    }
+
+    fn render(&self) -> String {
+        format!("{}\n\nCoins: {}", self.configuration.print_at(self.pulls), self.coins)
+    }
+}
+
+/// Quest 19: Each step is one round of decryption.
+#[derive(Clone)]
+struct Decryption<'a> {
+    message: Message,
+    cycles: &'a [Vec<(usize, usize)>],
+}
+
+impl Simulation for Decryption<'_> {
+    fn step(&mut self) {
+        self.message.apply_permutation_cycles(self.cycles, 1);
+    }
+
+    fn render(&self) -> String {
+        self.message.rows().collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Quest 20: Each step is one second of flight. We keep track of the highest altitude the glider
+/// can have at each position (and facing).
+#[derive(Clone)]
+struct Gliding<'a> {
+    map: &'a Map,
+    gliders: HashMap<((usize, usize), Direction), Glider>,
+}
+
+impl Simulation for Gliding<'_> {
+    fn step(&mut self) {
+        let mut next: HashMap<_, Glider> = HashMap::new();
+        self.gliders.values().flat_map(|glider| glider.moves(self.map)).for_each(|glider| {
+            let best = next.entry((glider.coordinates(), glider.facing())).or_insert(glider);
+            if best.altitude() < glider.altitude() {
+                *best = glider;
+            }
+        });
+        self.gliders = next;
+    }
+
+    fn render(&self) -> String {
+        let mut best_altitudes: HashMap<(usize, usize), usize> = HashMap::new();
+        self.gliders.values().for_each(|glider| {
+            let best = best_altitudes.entry(glider.coordinates()).or_default();
+            *best = glider.altitude().max(*best);
+        });
+        let max = best_altitudes.values().max().copied().unwrap_or(0);
+        let map = self.map.tiles()
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row
+                .iter()
+                .enumerate()
+                .map(|(x, tile)| match (best_altitudes.get(&(x, y)), tile) {
+                    (Some(altitude), _) if *altitude == max => '@',
+                    (Some(_), _) => '*',
+                    (None, Tile::Warm) => '+',
+                    (None, Tile::Cold) => '-',
+                    (None, Tile::Rock) => '#',
+                    (None, Tile::Stagnant) => '.',
+                }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("{map}\n\nMaximum altitude: {max} (@)")
+    }
+}
+
+/// Open the viewer for the given quest and part, if it is a step-wise simulation.
+pub fn view(quest: usize, input: &str, part: usize) -> Result<(), String> {
+    let interact = |res: std::io::Result<()>| res.map_err(|e| e.to_string());
+    match quest {
+        5 => {
+            let dancers = Dancers::try_from(input).map_err(|e| e.to_string())?;
+            interact(Viewer::new(ClapDance { dancers, shouted: None }).interact(stdin().lock(), stdout()))
+        },
+        11 => {
+            let model = PopulationModel::try_from(input).map_err(|e| e.to_string())?;
+            let start = match part {
+                1 => model.category("A"),
+                2 => model.category("Z"),
+                _ => Some(0),
+            }.ok_or("Starting category not found")?;
+            let mut population = vec![0; model.categories()];
+            population[start] = 1;
+            interact(Viewer::new(Termites { model: &model, population }).interact(stdin().lock(), stdout()))
+        },
+        16 => {
+            let configuration = Configuration::try_from(input).map_err(|e| e.to_string())?;
+            interact(Viewer::new(SlotMachine { configuration: &configuration, pulls: 0, coins: 0 }).interact(stdin().lock(), stdout()))
+        },
+        19 => {
+            let (key, message) = input.split_once("\n\n").ok_or("Input should be the key, followed by an empty line, and the message")?;
+            let key = Key::try_from(key).map_err(|e| e.to_string())?;
+            let message = Message::try_from(message).map_err(|e| e.to_string())?;
+            let cycles = message.permutation_cycles(&key);
+            interact(Viewer::new(Decryption { message, cycles: &cycles }).interact(stdin().lock(), stdout()))
+        },
+        20 => {
+            let map = Map::try_from(input).map_err(|e| e.to_string())?;
+            let altitude = match part {
+                1 => 1000,
+                2 => 10000,
+                _ => 384400,
+            };
+            let glider = Glider::new(map.starting(), altitude);
+            let gliders = HashMap::from([((glider.coordinates(), glider.facing()), glider)]);
+            interact(Viewer::new(Gliding { map: &map, gliders }).interact(stdin().lock(), stdout()))
+        },
+        _ => Err(format!("Quest {quest} is not a step-wise simulation. Try 5, 11, 16, 19 or 20.")),
+    }
+}
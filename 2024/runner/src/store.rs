@@ -0,0 +1,170 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where a stored note came from. `fetched` is in seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub source: String,
+    pub fetched: u64,
+    pub key: Option<String>,
+}
+
+impl Metadata {
+    /// Metadata for a note fetched right now.
+    pub fn now(source: &str, key: Option<&str>) -> Self {
+        let fetched = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self { source: source.to_string(), fetched, key: key.map(|k| k.to_string()) }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let mut source = None;
+        let mut fetched = None;
+        let mut key = None;
+        for line in value.lines() {
+            match line.split_once('=')? {
+                ("source", s) => source = Some(s.to_string()),
+                ("fetched", f) => fetched = Some(f.parse().ok()?),
+                ("key", k) => key = Some(k.to_string()),
+                _ => (),
+            }
+        }
+        Some(Self { source: source?, fetched: fetched?, key })
+    }
+
+    fn serialize(&self) -> String {
+        let mut res = format!("source={}\nfetched={}\n", self.source, self.fetched);
+        if let Some(key) = &self.key {
+            res += &format!("key={key}\n");
+        }
+        res
+    }
+}
+
+/// A note as found in the store.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Note {
+    pub hash: String,
+    pub content: String,
+    pub metadata: Metadata,
+}
+
+/// The 64 bit FNV-1a hash of `content` as a hex string. It's stable across platforms and Rust
+/// versions, which `std`'s `DefaultHasher` is not.
+pub fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    format!("{hash:016x}")
+}
+
+/// A local store for notes, keyed by (event, quest, part, content hash). Each note is kept in
+/// `<root>/<event>/<quest>/<part>/<hash>.txt`, with its metadata next to it in `<hash>.meta`.
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store in `$EC_NOTES_DIR` if set, or in `.notes` next to the quests otherwise.
+    pub fn open_default() -> Self {
+        match std::env::var_os("EC_NOTES_DIR") {
+            Some(dir) => Self::new(dir),
+            None => Self::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(".notes")),
+        }
+    }
+
+    fn dir(&self, event: &str, quest: usize, part: usize) -> PathBuf {
+        self.root.join(event).join(format!("{quest:02}")).join(part.to_string())
+    }
+
+    /// Store a note and return its content hash. Adding the same content again only updates its
+    /// metadata.
+    pub fn add(&self, event: &str, quest: usize, part: usize, content: &str, metadata: &Metadata) -> io::Result<String> {
+        let dir = self.dir(event, quest, part);
+        fs::create_dir_all(&dir)?;
+        let hash = content_hash(content);
+        fs::write(dir.join(format!("{hash}.txt")), content)?;
+        fs::write(dir.join(format!("{hash}.meta")), metadata.serialize())?;
+        Ok(hash)
+    }
+
+    pub fn get(&self, event: &str, quest: usize, part: usize, hash: &str) -> io::Result<Option<Note>> {
+        let dir = self.dir(event, quest, part);
+        let content = match fs::read_to_string(dir.join(format!("{hash}.txt"))) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let metadata = fs::read_to_string(dir.join(format!("{hash}.meta")))?;
+        let metadata = Metadata::parse(&metadata)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Malformed metadata for note {hash}")))?;
+        Ok(Some(Note { hash: hash.to_string(), content, metadata }))
+    }
+
+    /// The most recently fetched note for the given part, if any.
+    pub fn latest(&self, event: &str, quest: usize, part: usize) -> io::Result<Option<Note>> {
+        let entries = match fs::read_dir(self.dir(event, quest, part)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut latest: Option<Note> = None;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                let hash = path.file_stem().unwrap_or_default().to_string_lossy();
+                if let Some(note) = self.get(event, quest, part, &hash)? {
+                    if latest.as_ref().is_none_or(|l| (l.metadata.fetched, &l.hash) < (note.metadata.fetched, &note.hash)) {
+                        latest = Some(note);
+                    }
+                }
+            }
+        }
+        Ok(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> Store {
+        let root = std::env::temp_dir().join(format!("runner-store-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        Store::new(root)
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_add_latest() {
+        let store = temp_store("latest");
+        assert_eq!(store.latest("2024", 1, 1).unwrap(), None);
+
+        let old = Metadata { source: "alice".to_string(), fetched: 100, key: None };
+        let new = Metadata { source: "bob".to_string(), fetched: 200, key: Some("s3cr3t".to_string()) };
+        let old_hash = store.add("2024", 1, 1, "ABBAC", &old).unwrap();
+        let new_hash = store.add("2024", 1, 1, "xBxAAABCDxCC", &new).unwrap();
+        assert_ne!(old_hash, new_hash);
+
+        let latest = store.latest("2024", 1, 1).unwrap().unwrap();
+        assert_eq!(latest.content, "xBxAAABCDxCC");
+        assert_eq!(latest.metadata, new);
+        assert_eq!(store.get("2024", 1, 1, &old_hash).unwrap().unwrap().metadata, old);
+        assert_eq!(store.latest("2024", 1, 2).unwrap(), None);
+
+        // Re-adding the old content makes it the latest note again.
+        let newest = Metadata { fetched: 300, ..old };
+        assert_eq!(store.add("2024", 1, 1, "ABBAC", &newest).unwrap(), old_hash);
+        assert_eq!(store.latest("2024", 1, 1).unwrap().unwrap().hash, old_hash);
+
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+}
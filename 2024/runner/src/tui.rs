@@ -0,0 +1,133 @@
+use std::io::{BufRead, Write};
+
+/// A step-wise simulation, which can be displayed in the viewer.
+pub trait Simulation: Clone {
+    /// Advance the simulation by one step.
+    fn step(&mut self);
+
+    /// Render the current state for display.
+    fn render(&self) -> String;
+}
+
+// Every this many steps, the viewer stores a snapshot of the simulation. Rewinding restores the
+// closest snapshot before the target and replays the remaining steps from there.
+const SNAPSHOT_INTERVAL: usize = 64;
+
+const HELP: &str = "[Enter]/n: next step   b: back   j N: jump N steps   g N: go to step N   q: quit";
+
+/// Keeps track of a `Simulation` and its history, so it can be advanced and rewound.
+pub struct Viewer<S: Simulation> {
+    current: S,
+    step: usize,
+    snapshots: Vec<S>,
+}
+
+impl<S: Simulation> Viewer<S> {
+    pub fn new(simulation: S) -> Self {
+        Self { current: simulation.clone(), step: 0, snapshots: vec![simulation] }
+    }
+
+    /// Go to the given step, replaying from the closest snapshot if needed.
+    pub fn go_to(&mut self, target: usize) {
+        if target < self.step {
+            let snapshot = (target / SNAPSHOT_INTERVAL).min(self.snapshots.len() - 1);
+            self.current = self.snapshots[snapshot].clone();
+            self.step = snapshot * SNAPSHOT_INTERVAL;
+        }
+        while self.step < target {
+            self.current.step();
+            self.step += 1;
+            if self.step.is_multiple_of(SNAPSHOT_INTERVAL) && self.snapshots.len() == self.step / SNAPSHOT_INTERVAL {
+                self.snapshots.push(self.current.clone());
+            }
+        }
+    }
+
+    /// Read commands from `input` and write the rendered states to `output`, until the input
+    /// ends or the user quits.
+    pub fn interact(&mut self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        self.show(&mut output, "")?;
+        for line in input.lines() {
+            let line = line?;
+            let mut args = line.split_whitespace();
+            let command = args.next().unwrap_or("n");
+            let count = args.next().map(|n| n.parse::<usize>());
+            let message = match (command, count) {
+                ("q", _) => return Ok(()),
+                ("n", None) => { self.go_to(self.step + 1); "" },
+                ("b", None) => { self.go_to(self.step.saturating_sub(1)); "" },
+                ("j", Some(Ok(n))) => { self.go_to(self.step + n); "" },
+                ("g", Some(Ok(n))) => { self.go_to(n); "" },
+                _ => "Unknown command",
+            };
+            self.show(&mut output, message)?;
+        }
+        Ok(())
+    }
+
+    fn show(&self, output: &mut impl Write, message: &str) -> std::io::Result<()> {
+        // Clear the screen and move the cursor to the top left corner before drawing.
+        write!(output, "\x1b[2J\x1b[H")?;
+        writeln!(output, "Step {}\n\n{}\n\n{HELP}", self.step, self.current.render())?;
+        if !message.is_empty() {
+            writeln!(output, "{message}")?;
+        }
+        write!(output, "> ")?;
+        output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts the steps taken since it was last cloned, i. e. restored from a snapshot.
+    struct Counter {
+        value: usize,
+        replayed: usize,
+    }
+
+    impl Clone for Counter {
+        fn clone(&self) -> Self {
+            Self { value: self.value, replayed: 0 }
+        }
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) {
+            self.value = self.value * 3 % 1000;
+            self.replayed += 1;
+        }
+
+        fn render(&self) -> String {
+            self.value.to_string()
+        }
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut viewer = Viewer::new(Counter { value: 1, replayed: 0 });
+        viewer.go_to(200);
+        let at_200 = viewer.current.value;
+        viewer.go_to(130);
+        let at_130 = viewer.current.value;
+        // Rewinding must only replay from the last snapshot, not from the start.
+        assert_eq!(viewer.current.replayed, 130 - 128);
+        viewer.go_to(200);
+        assert_eq!(viewer.current.value, at_200);
+
+        let mut fresh = Viewer::new(Counter { value: 1, replayed: 0 });
+        fresh.go_to(130);
+        assert_eq!(fresh.current.value, at_130);
+    }
+
+    #[test]
+    fn test_interact() {
+        let mut viewer = Viewer::new(Counter { value: 1, replayed: 0 });
+        let mut output = Vec::new();
+        viewer.interact(&b"n\n\nj 5\nb\nfoo\ng 2\nq\nn\n"[..], &mut output).unwrap();
+        assert_eq!(viewer.step, 2);
+        assert_eq!(viewer.current.value, 9);
+        assert!(String::from_utf8(output).unwrap().contains("Unknown command"));
+    }
+}